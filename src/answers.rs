@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::solution::{Answer, Day};
+
+/// File holding the confirmed answers, relative to the crate root.
+pub const ANSWERS_FILE: &str = "answers.json";
+
+/// Name of a day's own puzzle input, `dN.txt` in the inputs directory.
+pub const PROBLEM: &str = "problem";
+
+/// Confirmed answers, by day, then input name, then part.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Answers(BTreeMap<u8, BTreeMap<String, BTreeMap<u8, Answer>>>);
+
+/// How an answer compares with the confirmed one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Wrong {
+        expected: Answer,
+    },
+    /// Nothing has been confirmed for this day, input and part yet.
+    Unverified,
+}
+
+impl Answers {
+    /// Reads the answers stored at `path`; a missing file holds no answers.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Answers::default());
+        }
+        let json = fs::read_to_string(path).map_err(|err| inaccessible(path, err))?;
+        serde_json::from_str(&json).map_err(|err| invalid(path, err))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|err| invalid(path, err))?;
+        fs::write(path, json + "\n").map_err(|err| inaccessible(path, err))
+    }
+
+    pub fn expected(&self, day: u8, input: &str, part: u8) -> Option<&Answer> {
+        self.0.get(&day)?.get(input)?.get(&part)
+    }
+
+    pub fn verify(&self, day: u8, input: &str, part: u8, answer: &Answer) -> Verdict {
+        match self.expected(day, input, part) {
+            Some(expected) if expected == answer => Verdict::Correct,
+            Some(expected) => Verdict::Wrong {
+                expected: expected.clone(),
+            },
+            None => Verdict::Unverified,
+        }
+    }
+
+    /// Stores a newly confirmed answer, returning the one it replaces.
+    pub fn record(&mut self, day: u8, input: &str, part: u8, answer: Answer) -> Option<Answer> {
+        self.0
+            .entry(day)
+            .or_default()
+            .entry(input.to_string())
+            .or_default()
+            .insert(part, answer)
+    }
+
+    /// The `(day, part)`s of `days` with no confirmed answer for the input named `input`,
+    /// [`PROBLEM`] for their own puzzle input.
+    pub fn unverified(&self, days: &[Day], input: &str) -> Vec<(u8, u8)> {
+        days.iter()
+            .flat_map(|day| [(day.day, 1), (day.day, 2)])
+            .filter(|&(day, part)| self.expected(day, input, part).is_none())
+            .collect()
+    }
+}
+
+fn inaccessible(path: &Path, err: impl Display) -> Error {
+    Error::other(format!("cannot access {}: {}", path.display(), err))
+}
+
+fn invalid(path: &Path, err: impl Display) -> Error {
+    Error::other(format!("invalid answers in {}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Source, DEFAULT_INPUTS_DIR};
+    use crate::solution::REGISTRY;
+
+    const RECORDED: &str = include_str!("../answers.json");
+
+    #[test]
+    fn test_verify_and_record() {
+        let mut answers = Answers::default();
+        assert_eq!(
+            answers.verify(7, PROBLEM, 1, &37.into()),
+            Verdict::Unverified
+        );
+
+        assert_eq!(answers.record(7, PROBLEM, 1, 37.into()), None);
+        assert_eq!(answers.verify(7, PROBLEM, 1, &37.into()), Verdict::Correct);
+        assert_eq!(
+            answers.verify(7, PROBLEM, 1, &36.into()),
+            Verdict::Wrong {
+                expected: 37.into()
+            }
+        );
+        assert_eq!(
+            answers.verify(7, "d7_example", 1, &37.into()),
+            Verdict::Unverified
+        );
+
+        assert_eq!(answers.record(7, PROBLEM, 1, 36.into()), Some(37.into()));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut answers = Answers::default();
+        answers.record(7, PROBLEM, 2, 168.into());
+        answers.record(13, PROBLEM, 2, "#  #\n####\n".to_string().into());
+
+        let json = serde_json::to_string(&answers).unwrap();
+        assert_eq!(
+            json,
+            r##"{"7":{"problem":{"2":168}},"13":{"problem":{"2":"#  #\n####\n"}}}"##
+        );
+        assert_eq!(serde_json::from_str::<Answers>(&json).unwrap(), answers);
+    }
+
+    #[test]
+    fn test_unverified() {
+        let mut answers = Answers::default();
+        answers.record(1, PROBLEM, 1, 7.into());
+        answers.record(1, PROBLEM, 2, 5.into());
+        answers.record(2, "d2_example", 1, 150.into());
+
+        assert_eq!(
+            answers.unverified(&REGISTRY[..2], PROBLEM),
+            [(2, 1), (2, 2)]
+        );
+        assert_eq!(
+            answers.unverified(&REGISTRY[..2], "d2_example"),
+            [(1, 1), (1, 2), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn test_recorded_answers() {
+        let answers: Answers = serde_json::from_str(RECORDED).unwrap();
+        let source = Source::Dir(DEFAULT_INPUTS_DIR.into());
+        for day in REGISTRY {
+            for part in 1..=2 {
+                if let Some(expected) = answers.expected(day.day, PROBLEM, part) {
+                    let input = source.load(day.day).unwrap();
+                    assert_eq!(
+                        day.run(part, &input).as_ref(),
+                        Ok(expected),
+                        "day {} part {}",
+                        day.day,
+                        part
+                    );
+                }
+            }
+        }
+    }
+}
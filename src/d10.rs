@@ -1,3 +1,26 @@
+use crate::error::{Error, Result};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 10;
+
+pub fn parse_input(input: &str) -> Result<Vec<String>> {
+    input
+        .lines()
+        .map(|line| match line.find(|c| !"()[]{}<>".contains(c)) {
+            Some(i) => Err(Error::at(
+                DAY,
+                input,
+                &line[i..],
+                format!(
+                    "unexpected character `{}`",
+                    &line[i..].chars().next().unwrap_or_default()
+                ),
+            )),
+            None => Ok(line.to_string()),
+        })
+        .collect()
+}
+
 fn illegal_char(line: &str) -> Option<char> {
     let mut close = vec![];
     for c in line.chars() {
@@ -58,23 +81,53 @@ fn score_completion(s: String) -> usize {
     })
 }
 
-pub fn p1(input: &str) -> usize {
-    input
-        .lines()
-        .filter_map(illegal_char)
+pub fn p1(input: &str) -> Result<usize> {
+    Ok(syntax_error_score(&parse_input(input)?))
+}
+
+pub fn p2(input: &str) -> Result<usize> {
+    middle_completion_score(&parse_input(input)?)
+}
+
+fn syntax_error_score(lines: &[String]) -> usize {
+    lines
+        .iter()
+        .filter_map(|line| illegal_char(line))
         .map(score_corrupted)
         .sum()
 }
 
-pub fn p2(input: &str) -> usize {
-    let mut scores = input
-        .lines()
-        .filter_map(completion_chars)
+fn middle_completion_score(lines: &[String]) -> Result<usize> {
+    let mut scores = lines
+        .iter()
+        .filter_map(|line| completion_chars(line))
         .map(score_completion)
         .collect::<Vec<usize>>();
 
     scores.sort_unstable();
-    scores[scores.len() / 2]
+    scores
+        .get(scores.len() / 2)
+        .copied()
+        .ok_or_else(|| Error::new(DAY, "no line is incomplete"))
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = DAY;
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(lines: &Self::Input) -> Result<Answer> {
+        Ok(syntax_error_score(lines).into())
+    }
+
+    fn part2(lines: &Self::Input) -> Result<Answer> {
+        Ok(middle_completion_score(lines)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -94,24 +147,36 @@ mod tests {
 
     #[test]
     fn test_p1_example() {
-        assert_eq!(p1(EXAMPLE), 26397);
+        assert_eq!(p1(EXAMPLE), Ok(26397));
     }
 
     #[test]
     fn test_p1() {
         let input = include_str!("inputs/d10.txt");
-        assert_eq!(p1(input), 392097);
+        assert_eq!(p1(input), Ok(392097));
     }
 
     #[test]
     fn test_p2_example() {
         let input = EXAMPLE;
-        assert_eq!(p2(input), 288957);
+        assert_eq!(p2(input), Ok(288957));
     }
 
     #[test]
     fn test_p2() {
         let input = include_str!("inputs/d10.txt");
-        assert_eq!(p2(input), 4263222782);
+        assert_eq!(p2(input), Ok(4263222782));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("[({(<(())[]>[[{[]{<()<>>\n[(()[<>])]x({[<{<<[]>>(\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 10, line 2, column 11: unexpected character `x`"
+        );
+
+        let err = p2("{([(<{}[<>[]}>{[]{[(<()>\n").unwrap_err();
+        assert_eq!(err.to_string(), "day 10: no line is incomplete");
     }
 }
@@ -1,68 +1,67 @@
-#[rustfmt::skip]
-const NEXT: [(isize, isize); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
-const SIZE: usize = 10;
-
-pub fn parse_input(input: &str) -> Vec<Vec<u32>> {
-    input
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|c| c.to_digit(10).unwrap())
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>()
-}
+use crate::error::Result;
+use crate::grid::{Grid, Point};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 11;
+
+pub type Octopuses = Grid<u8>;
 
-fn flash(map: &mut Vec<Vec<u32>>, x: usize, y: usize) -> usize {
-    map[y][x] = 0;
-    NEXT.iter()
-        .map(|(xx, yy)| ((x as isize + xx) as usize, (y as isize + yy) as usize))
-        .fold(1, |acc, (x, y)| {
-            match map.get_mut(y).and_then(|l| l.get_mut(x)) {
-                Some(cell) if *cell > 0 => {
-                    *cell += 1;
-                    acc + (*cell > 9).then(|| flash(map, x, y)).unwrap_or(0)
-                }
-                _ => acc,
-            }
-        })
+pub fn parse_input(input: &str) -> Result<Octopuses> {
+    Grid::parse_digits(DAY, input)
 }
 
-pub fn p1(input: &str) -> usize {
-    let mut parsed_input = parse_input(input);
-
-    (0..100).fold(0, |acc, _| {
-        parsed_input
-            .iter_mut()
-            .for_each(|row| row.iter_mut().for_each(|cell| *cell += 1));
-        acc + (0..SIZE)
-            .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
-            .fold(0, |acc, (x, y)| {
-                acc + (parsed_input[y][x] > 9)
-                    .then(|| flash(&mut parsed_input, x, y))
-                    .unwrap_or(0)
-            })
+fn flash(map: &mut Octopuses, p: Point) -> usize {
+    map[p] = 0;
+    map.neighbours8(p).fold(1, |acc, n| match map[n] {
+        0 => acc,
+        _ => {
+            map[n] += 1;
+            acc + if map[n] > 9 { flash(map, n) } else { 0 }
+        }
     })
 }
 
-pub fn p2(input: &str) -> usize {
-    let mut parsed_input = parse_input(input);
-
-    (1..)
-        .find(|_| {
-            parsed_input
-                .iter_mut()
-                .for_each(|row| row.iter_mut().for_each(|cell| *cell += 1));
-            (0..SIZE)
-                .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
-                .fold(0, |acc, (x, y)| {
-                    acc + (parsed_input[y][x] > 9)
-                        .then(|| flash(&mut parsed_input, x, y))
-                        .unwrap_or(0)
-                })
-                == SIZE * SIZE
-        })
-        .unwrap()
+/// Advances one step, returning how many octopuses flashed.
+fn step(map: &mut Octopuses) -> usize {
+    map.iter_mut().for_each(|cell| *cell += 1);
+    map.points()
+        .fold(0, |acc, p| acc + if map[p] > 9 { flash(map, p) } else { 0 })
+}
+
+pub fn p1(input: &str) -> Result<usize> {
+    Ok(count_flashes(parse_input(input)?))
+}
+
+pub fn p2(input: &str) -> Result<usize> {
+    Ok(first_synchronized_step(parse_input(input)?))
+}
+
+fn count_flashes(mut octopuses: Octopuses) -> usize {
+    (0..100).map(|_| step(&mut octopuses)).sum()
+}
+
+fn first_synchronized_step(mut octopuses: Octopuses) -> usize {
+    let all = octopuses.len();
+    (1..).find(|_| step(&mut octopuses) == all).unwrap()
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = DAY;
+    type Input = Octopuses;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(octopuses: &Self::Input) -> Result<Answer> {
+        Ok(count_flashes(octopuses.clone()).into())
+    }
+
+    fn part2(octopuses: &Self::Input) -> Result<Answer> {
+        Ok(first_synchronized_step(octopuses.clone()).into())
+    }
 }
 
 #[cfg(test)]
@@ -88,21 +87,33 @@ mod tests {
 
     #[test]
     fn test_example_p1() {
-        assert_eq!(p1(EXAMPLE_INPUT), EXAMPLE_P1_ANSWER);
+        assert_eq!(p1(EXAMPLE_INPUT), Ok(EXAMPLE_P1_ANSWER));
     }
 
     #[test]
     fn test_example_p2() {
-        assert_eq!(p2(EXAMPLE_INPUT), EXAMPLE_P2_ANSWER);
+        assert_eq!(p2(EXAMPLE_INPUT), Ok(EXAMPLE_P2_ANSWER));
     }
 
     #[test]
     fn test_problem_p1() {
-        assert_eq!(p1(PROBLEM_INPUT), PROBLEM_P1_ANSWER);
+        assert_eq!(p1(PROBLEM_INPUT), Ok(PROBLEM_P1_ANSWER));
     }
 
     #[test]
     fn test_problem_p2() {
-        assert_eq!(p2(PROBLEM_INPUT), PROBLEM_P2_ANSWER);
+        assert_eq!(p2(PROBLEM_INPUT), Ok(PROBLEM_P2_ANSWER));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1(&EXAMPLE_INPUT.replacen('4', "x", 1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 11, line 1, column 2: expected a digit, found `x`"
+        );
+
+        let err = p1("5483143223\n274585471\n").unwrap_err();
+        assert_eq!(err.reason, "expected 10 cells, found 9");
     }
 }
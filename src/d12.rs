@@ -1,10 +1,38 @@
 use std::collections::HashMap;
 
+use crate::error::{split_once, Error, Result};
+use crate::search;
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 12;
+
 const START: usize = 0;
 const END: usize = 1;
 
-fn parse_input(input: &str) -> Vec<Vec<usize>> {
-    let id = input.trim().lines().flat_map(|line| line.split('-')).fold(
+fn is_big(cave: &str) -> bool {
+    cave.chars().any(|c| c.is_uppercase())
+}
+
+fn parse_input(input: &str) -> Result<Vec<Vec<usize>>> {
+    let edges = input
+        .trim()
+        .lines()
+        .map(|line| {
+            let (l, r) = split_once(DAY, input, line, "-")?;
+            if is_big(l) && is_big(r) {
+                // the walk could bounce between them forever
+                return Err(Error::at(
+                    DAY,
+                    input,
+                    line,
+                    format!("big caves `{}` and `{}` are connected", l, r),
+                ));
+            }
+            Ok((l, r))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let id = edges.iter().flat_map(|&(l, r)| [l, r]).fold(
         HashMap::from([("start", START), ("end", END)]),
         |mut acc, cave| {
             let next_id = acc.len();
@@ -12,10 +40,16 @@ fn parse_input(input: &str) -> Vec<Vec<usize>> {
             acc
         },
     );
+    if id.len() > u64::BITS as usize {
+        return Err(Error::new(
+            DAY,
+            format!("{} caves do not fit in a 64 bit visited set", id.len()),
+        ));
+    }
 
-    let mut caves = input
-        .lines()
-        .filter_map(|line| line.split_once('-').map(|(l, r)| (id[l], id[r])))
+    let mut caves = edges
+        .iter()
+        .map(|(l, r)| (id[l], id[r]))
         .flat_map(|(l, r)| [(l, r), (r, l)])
         .filter(|(l, r)| l != &END && r != &START)
         .fold(vec![vec![]; id.len()], |mut caves, (l, r)| {
@@ -25,7 +59,7 @@ fn parse_input(input: &str) -> Vec<Vec<usize>> {
 
     // flatten big caves
     id.iter()
-        .filter(|&(name, _)| name.chars().any(|c| c.is_uppercase()))
+        .filter(|&(name, _)| is_big(name))
         .for_each(|(_, &big)| {
             let smalls = caves[big].clone();
             caves.iter_mut().for_each(|nexts| {
@@ -34,37 +68,56 @@ fn parse_input(input: &str) -> Vec<Vec<usize>> {
                 }
             });
         });
-    caves
+    Ok(caves)
 }
 
-fn dfs(caves: &[Vec<usize>], visited: &mut Vec<bool>, curr: usize, extra: usize) -> usize {
-    caves[curr].iter().fold(0, |acc, &next| match next {
-        END => acc + 1,
-        _ => match visited[next] {
-            true => match extra {
-                0 => acc,
-                _ => acc + dfs(caves, visited, next, extra - 1),
-            },
-            _ => {
-                visited[next] = true;
-                let paths = dfs(caves, visited, next, extra);
-                visited[next] = false;
-                acc + paths
-            }
+/// Counts the walks from `start` to `end`, allowing `extra` repeated visits to small caves.
+///
+/// A walk is identified by where it is, which caves it has seen and how many repeats it has
+/// left, so each step either marks a new cave or spends a repeat and the walks never loop.
+fn count_paths(caves: &[Vec<usize>], extra: usize) -> usize {
+    search::count_paths(
+        (START, 0u64, extra),
+        |&(curr, visited, extra)| {
+            caves[curr].iter().filter_map(move |&next| {
+                let seen = visited & 1 << next != 0;
+                match (next, seen, extra) {
+                    (END, _, _) => Some((END, visited, extra)),
+                    (_, true, 0) => None,
+                    (_, true, _) => Some((next, visited, extra - 1)),
+                    (_, false, _) => Some((next, visited | 1 << next, extra)),
+                }
+            })
         },
-    })
+        |&(curr, _, _)| curr == END,
+    )
 }
 
-pub fn p1(input: &str) -> usize {
-    let caves = parse_input(input);
+pub fn p1(input: &str) -> Result<usize> {
+    Ok(count_paths(&parse_input(input)?, 0))
+}
 
-    println!("{:?}", caves);
-    dfs(&caves, &mut vec![false; caves.len()], START, 0)
+pub fn p2(input: &str) -> Result<usize> {
+    Ok(count_paths(&parse_input(input)?, 1))
 }
 
-pub fn p2(input: &str) -> usize {
-    let caves = parse_input(input);
-    dfs(&caves, &mut vec![false; caves.len()], START, 1)
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = DAY;
+    type Input = Vec<Vec<usize>>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(caves: &Self::Input) -> Result<Answer> {
+        Ok(count_paths(caves, 0).into())
+    }
+
+    fn part2(caves: &Self::Input) -> Result<Answer> {
+        Ok(count_paths(caves, 1).into())
+    }
 }
 
 #[cfg(test)]
@@ -87,21 +140,33 @@ b-end"#;
 
     #[test]
     fn test_example_p1() {
-        assert_eq!(p1(EXAMPLE_INPUT), EXAMPLE_P1_ANSWER);
+        assert_eq!(p1(EXAMPLE_INPUT), Ok(EXAMPLE_P1_ANSWER));
     }
 
     #[test]
     fn test_example_p2() {
-        assert_eq!(p2(EXAMPLE_INPUT), EXAMPLE_P2_ANSWER);
+        assert_eq!(p2(EXAMPLE_INPUT), Ok(EXAMPLE_P2_ANSWER));
     }
 
     #[test]
     fn test_problem_p1() {
-        assert_eq!(p1(PROBLEM_INPUT), PROBLEM_P1_ANSWER);
+        assert_eq!(p1(PROBLEM_INPUT), Ok(PROBLEM_P1_ANSWER));
     }
 
     #[test]
     fn test_problem_p2() {
-        assert_eq!(p2(PROBLEM_INPUT), PROBLEM_P2_ANSWER);
+        assert_eq!(p2(PROBLEM_INPUT), Ok(PROBLEM_P2_ANSWER));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("start-A\nA b\nA-end").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 12, line 2, column 1: expected `-` in `A b`"
+        );
+
+        let err = p1("start-A\nA-B\nA-end").unwrap_err();
+        assert_eq!(err.reason, "big caves `A` and `B` are connected");
     }
 }
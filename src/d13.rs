@@ -1,71 +1,105 @@
 use std::collections::HashSet;
 
-fn parse_input(input: &str) -> (HashSet<(usize, usize)>, Vec<(u8, usize)>) {
-    let (coords, folds) = input.trim().split_once("\n\n").unwrap();
+use crate::error::{parse_number, split_once, Error, Result};
+use crate::solution::{Answer, Solution};
 
-    let coords: HashSet<(usize, usize)> = coords
+const DAY: u8 = 13;
+
+pub type Dots = HashSet<(usize, usize)>;
+pub type Folds = Vec<(u8, usize)>;
+
+fn parse_input(input: &str) -> Result<(Dots, Folds)> {
+    let (coords, folds) = split_once(DAY, input, input.trim(), "\n\n")
+        .map_err(|_| Error::new(DAY, "expected a blank line between dots and folds"))?;
+
+    let coords: Dots = coords
         .lines()
         .map(|l| {
-            let coord = l.split_once(',').unwrap();
-            (
-                coord.0.parse::<usize>().unwrap(),
-                coord.1.parse::<usize>().unwrap(),
-            )
+            let coord = split_once(DAY, input, l, ",")?;
+            Ok((
+                parse_number(DAY, input, coord.0)?,
+                parse_number(DAY, input, coord.1)?,
+            ))
         })
-        .collect();
+        .collect::<Result<_>>()?;
 
     let folds = folds
         .lines()
-        .map(|l| l.trim_start_matches("fold along ").split_once('=').unwrap())
-        .map(|(c, i)| (c.as_bytes()[0], i.parse::<usize>().unwrap()))
-        .collect::<Vec<_>>();
+        .map(|l| split_once(DAY, input, l.trim_start_matches("fold along "), "="))
+        .map(|fold| {
+            let (c, i) = fold?;
+            match c {
+                "x" | "y" => Ok((c.as_bytes()[0], parse_number(DAY, input, i)?)),
+                _ => Err(Error::at(
+                    DAY,
+                    input,
+                    c,
+                    format!("expected a fold along x or y, found `{}`", c),
+                )),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((coords, folds))
+}
 
-    (coords, folds)
+/// Mirrors `coord` across the fold line at `at`, if it lands on the paper.
+fn reflect(coord: usize, at: usize) -> Result<usize> {
+    at.checked_sub(coord - at).ok_or_else(|| {
+        Error::new(
+            DAY,
+            format!("folding along {} sends {} off the paper", at, coord),
+        )
+    })
 }
 
-fn fold(hash_set: &mut HashSet<(usize, usize)>, fold: &(u8, usize)) {
+fn fold(hash_set: &mut HashSet<(usize, usize)>, fold: &(u8, usize)) -> Result<()> {
     let points = hash_set.iter().copied().collect::<Vec<(usize, usize)>>();
     for point in points {
         match fold.0 {
             b'y' => {
                 if point.1 > fold.1 {
                     hash_set.remove(&point);
-                    hash_set.insert((point.0, fold.1 - (point.1 - fold.1)));
+                    hash_set.insert((point.0, reflect(point.1, fold.1)?));
                 }
             }
             _ => {
                 if point.0 > fold.1 {
                     hash_set.remove(&point);
-                    hash_set.insert((fold.1 - (point.0 - fold.1), point.1));
+                    hash_set.insert((reflect(point.0, fold.1)?, point.1));
                 }
             }
         }
     }
+    Ok(())
+}
+
+pub fn p1(input: &str) -> Result<usize> {
+    let (coords, folds) = parse_input(input)?;
+    dots_after_first_fold(coords, &folds)
 }
 
-pub fn p1(input: &str) -> usize {
-    let (mut coords, folds) = parse_input(input);
-    fold(&mut coords, &folds[0]);
+pub fn p2(input: &str) -> Result<String> {
+    let (coords, folds) = parse_input(input)?;
+    code(coords, &folds)
+}
+
+fn dots_after_first_fold(mut coords: Dots, folds: &Folds) -> Result<usize> {
+    let first = folds
+        .first()
+        .ok_or_else(|| Error::new(DAY, "there are no fold instructions"))?;
+    fold(&mut coords, first)?;
 
-    coords.len()
+    Ok(coords.len())
 }
 
-pub fn p2(input: &str) -> String {
-    let (mut coords, folds) = parse_input(input);
+fn code(mut coords: Dots, folds: &Folds) -> Result<String> {
     for cur_fold in folds {
-        fold(&mut coords, &cur_fold);
+        fold(&mut coords, cur_fold)?;
     }
 
-    let max_x = coords
-        .iter()
-        .max_by(|point1, point2| point1.0.cmp(&point2.0))
-        .unwrap()
-        .0;
-    let max_y = coords
-        .iter()
-        .max_by(|point1, point2| point1.1.cmp(&point2.1))
-        .unwrap()
-        .1;
+    let max_x = coords.iter().map(|point| point.0).max().unwrap_or(0);
+    let max_y = coords.iter().map(|point| point.1).max().unwrap_or(0);
 
     let mut str: String = String::new();
     for y in 0..=max_y {
@@ -79,7 +113,26 @@ pub fn p2(input: &str) -> String {
         str.push('\n');
     }
 
-    str
+    Ok(str)
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = DAY;
+    type Input = (Dots, Folds);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1((coords, folds): &Self::Input) -> Result<Answer> {
+        Ok(dots_after_first_fold(coords.clone(), folds)?.into())
+    }
+
+    fn part2((coords, folds): &Self::Input) -> Result<Answer> {
+        Ok(code(coords.clone(), folds)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -96,23 +149,38 @@ mod tests {
 
     #[test]
     fn test_example_p1() {
-        assert_eq!(p1(EXAMPLE_INPUT), EXAMPLE_P1_ANSWER);
+        assert_eq!(p1(EXAMPLE_INPUT), Ok(EXAMPLE_P1_ANSWER));
     }
 
     #[test]
     fn test_example_p2() {
-        assert_eq!(p2(EXAMPLE_INPUT), EXAMPLE_P2_ANSWER);
+        assert_eq!(p2(EXAMPLE_INPUT), Ok(EXAMPLE_P2_ANSWER.to_string()));
     }
 
     #[test]
     fn test_problem_p1() {
-        assert_eq!(p1(PROBLEM_INPUT), PROBLEM_P1_ANSWER);
+        assert_eq!(p1(PROBLEM_INPUT), Ok(PROBLEM_P1_ANSWER));
     }
 
     #[test]
     fn test_problem_p2() {
-        let answer = p2(PROBLEM_INPUT);
+        let answer = p2(PROBLEM_INPUT).unwrap();
         println!("{}", answer);
-        assert_eq!(p2(PROBLEM_INPUT), PROBLEM_P2_ANSWER);
+        assert_eq!(p2(PROBLEM_INPUT), Ok(PROBLEM_P2_ANSWER.to_string()));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("6,10\n0,14\n\nfold along z=7\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 13, line 4, column 12: expected a fold along x or y, found `z`"
+        );
+
+        let err = p1("6,10\n\nfold along y=3\n").unwrap_err();
+        assert_eq!(err.reason, "folding along 3 sends 10 off the paper");
+
+        let err = p1("6,10\n0,14\n").unwrap_err();
+        assert_eq!(err.location, None);
     }
 }
@@ -2,8 +2,13 @@ use std::{collections::HashMap, fmt::Display};
 
 use itertools::{Itertools, MinMaxResult};
 
-type Seq = HashMap<(char, char), usize>;
-type Rules = HashMap<(char, char), char>;
+use crate::error::{split_once, Error, Result};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 14;
+
+pub type Seq = HashMap<(char, char), usize>;
+pub type Rules = HashMap<(char, char), char>;
 
 pub struct S(Seq);
 
@@ -19,35 +24,43 @@ impl Display for S {
     }
 }
 
-fn parse_input(input: &str) -> (Rules, Seq) {
-    let (start_seq, pairs) = input.trim().split_once("\n\n").unwrap();
+fn parse_input(input: &str) -> Result<(Rules, Seq)> {
+    let (start_seq, pairs) = split_once(DAY, input, input.trim(), "\n\n")
+        .map_err(|_| Error::new(DAY, "expected a blank line after the template"))?;
 
     let rules = pairs
         .lines()
-        .filter_map(|l| l.split(" -> ").collect_tuple::<(&str, &str)>())
-        .map(|(lhs, rhs)| {
-            (
-                lhs.chars().collect_tuple::<(char, char)>().unwrap(),
-                rhs.chars().next().unwrap(),
-            )
+        .map(|l| {
+            let (lhs, rhs) = split_once(DAY, input, l, " -> ")?;
+            match (
+                lhs.chars().collect_tuple::<(char, char)>(),
+                rhs.chars().collect_tuple::<(char,)>(),
+            ) {
+                (Some(pair), Some((insert,))) => Ok((pair, insert)),
+                _ => Err(Error::at(
+                    DAY,
+                    input,
+                    l,
+                    format!("expected a rule like `AB -> C`, found `{}`", l),
+                )),
+            }
         })
-        .collect();
+        .collect::<Result<_>>()?;
 
     let seq = format!("_{}_", start_seq).chars().tuple_windows().counts();
 
-    (rules, seq)
+    Ok((rules, seq))
 }
 
-pub fn grow_n_count(input: &str, i: usize) -> usize {
-    let (rules, init_seq) = parse_input(input);
-
-    println!("{:?}", init_seq);
+pub fn grow_n_count(input: &str, i: usize) -> Result<usize> {
+    let (rules, init_seq) = parse_input(input)?;
+    Ok(grow(&rules, init_seq, i))
+}
 
+fn grow(rules: &Rules, init_seq: Seq, i: usize) -> usize {
     let seq = (0..i).fold(init_seq, |seq, _i| {
         let mut new_seq = HashMap::new();
 
-        println!("after {} step: {}", _i, S(seq.clone()));
-
         for ((c1, c2), count) in seq {
             if let Some(&c_mid) = rules.get(&(c1, c2)) {
                 *new_seq.entry((c1, c_mid)).or_insert(0) += count;
@@ -59,8 +72,6 @@ pub fn grow_n_count(input: &str, i: usize) -> usize {
         new_seq
     });
 
-    println!("after {} step: {}", i, S(seq.clone()));
-
     let mut counts = HashMap::new();
     for ((c1, c2), count) in seq {
         *counts.entry(c1).or_insert(0) += count;
@@ -74,14 +85,33 @@ pub fn grow_n_count(input: &str, i: usize) -> usize {
     }
 }
 
-pub fn p1(input: &str) -> usize {
+pub fn p1(input: &str) -> Result<usize> {
     grow_n_count(input, 10)
 }
 
-pub fn p2(input: &str) -> usize {
+pub fn p2(input: &str) -> Result<usize> {
     grow_n_count(input, 40)
 }
 
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = DAY;
+    type Input = (Rules, Seq);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1((rules, seq): &Self::Input) -> Result<Answer> {
+        Ok(grow(rules, seq.clone(), 10).into())
+    }
+
+    fn part2((rules, seq): &Self::Input) -> Result<Answer> {
+        Ok(grow(rules, seq.clone(), 40).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,21 +144,36 @@ CN -> C
 
     #[test]
     fn test_example_p1() {
-        assert_eq!(p1(EXAMPLE_INPUT), EXAMPLE_P1_ANSWER);
+        assert_eq!(p1(EXAMPLE_INPUT), Ok(EXAMPLE_P1_ANSWER));
     }
 
     #[test]
     fn test_example_p2() {
-        assert_eq!(p2(EXAMPLE_INPUT), EXAMPLE_P2_ANSWER);
+        assert_eq!(p2(EXAMPLE_INPUT), Ok(EXAMPLE_P2_ANSWER));
     }
 
     #[test]
     fn test_problem_p1() {
-        assert_eq!(p1(PROBLEM_INPUT), PROBLEM_P1_ANSWER);
+        assert_eq!(p1(PROBLEM_INPUT), Ok(PROBLEM_P1_ANSWER));
     }
 
     #[test]
     fn test_problem_p2() {
-        assert_eq!(p2(PROBLEM_INPUT), PROBLEM_P2_ANSWER);
+        assert_eq!(p2(PROBLEM_INPUT), Ok(PROBLEM_P2_ANSWER));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("NNCB\n\nCH -> B\nHH => N\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 14, line 4, column 1: expected ` -> ` in `HH => N`"
+        );
+
+        let err = p1("NNCB\n\nCH -> B\nHHH -> N\n").unwrap_err();
+        assert_eq!(
+            err.reason,
+            "expected a rule like `AB -> C`, found `HHH -> N`"
+        );
     }
 }
@@ -1,93 +1,59 @@
-use std::collections::HashSet;
-use std::mem;
+use crate::error::Result;
+use crate::grid::Grid;
+use crate::search;
+use crate::solution::{Answer, Solution};
 
-pub struct Grid {
-    width: usize,
-    height: usize,
-    data: Box<[usize]>,
-}
+const DAY: u8 = 15;
 
-impl Grid {
-    pub fn parse_input(text: &[u8]) -> Self {
-        let height = text.split(|&c| c == b'\n').count() - 1; // off by one, idk
-        let width = text.split(|&c| c == b'\n').next().unwrap().len();
-        let data = text
-            .iter()
-            .filter(|&&c| c != b'\n')
-            .map(|c| usize::from(c - b'0'))
-            .collect();
-        Self {
-            height,
-            width,
-            data,
-        }
-    }
+pub type Cave = Grid<usize>;
 
-    pub fn bfs(&self) -> usize {
-        let mut flood = Self {
-            width: self.width,
-            height: self.height,
-            data: self.data.iter().map(|_| usize::MAX).collect(),
-        };
-        flood.data[0] = 0;
-        let mut scan = HashSet::new();
-        let mut scan_next = HashSet::new();
-        scan.insert((0, 0));
-        loop {
-            if scan.is_empty() {
-                return flood.get(self.width - 1, self.height - 1).unwrap();
-            }
-            for (x, y) in scan.drain() {
-                let v = flood.get(x, y).unwrap();
-                let mut f = |x, y| {
-                    let v = v + self.get(x, y).unwrap();
-                    if v < flood.get(x, y).unwrap() {
-                        flood.set(x, y, v).unwrap();
-                        scan_next.insert((x, y));
-                    }
-                };
-                (x < self.width - 1).then(|| f(x + 1, y));
-                (y < self.height - 1).then(|| f(x, y + 1));
-                (x > 0).then(|| f(x - 1, y));
-                (y > 0).then(|| f(x, y - 1));
-            }
-            mem::swap(&mut scan, &mut scan_next);
-        }
-    }
+pub fn parse_input(input: &str) -> Result<Cave> {
+    Grid::parse(DAY, input, "a risk level", |c| {
+        c.to_digit(10).filter(|&d| d > 0).map(|d| d as usize)
+    })
+}
 
-    pub fn enlarge(self, n: usize) -> Self {
-        let (sx, sy) = (self.width, self.height);
-        let f = |x, y, mx, my| -> usize { (mx + my + self.get(x, y).unwrap() - 1) % 9 + 1 };
-        Self {
-            width: self.width * n,
-            height: self.height * n,
-            data: (0..n)
-                .flat_map(move |my| {
-                    (0..sy).flat_map(move |y| {
-                        (0..n).flat_map(move |mx| (0..sx).map(move |x| f(x, y, mx, my)))
-                    })
-                })
-                .collect(),
-        }
-    }
+/// Total risk of the least risky path from the top left to the bottom right.
+pub fn lowest_total_risk(cave: &Cave) -> usize {
+    let exit = (cave.width() - 1, cave.height() - 1);
+    let (_, risk) = search::dijkstra(
+        (0, 0),
+        |&p| cave.neighbours4(p).map(|n| (n, cave[n])),
+        |&p| p == exit,
+    )
+    .expect("every cell of the cave is reachable");
+    risk
+}
 
-    fn get(&self, x: usize, y: usize) -> Option<usize> {
-        (x < self.width && y < self.height).then(|| self.data[y * self.width + x])
-    }
+pub fn enlarge(cave: &Cave, n: usize) -> Cave {
+    cave.tile(n, n, |risk, (mx, my)| (mx + my + risk - 1) % 9 + 1)
+}
 
-    fn set(&mut self, x: usize, y: usize, value: usize) -> Option<()> {
-        (x < self.width && y < self.height).then(|| self.data[y * self.width + x] = value)
-    }
+pub fn p1(input: &str) -> Result<usize> {
+    Ok(lowest_total_risk(&parse_input(input)?))
 }
 
-pub fn p1(input: &str) -> usize {
-    let grid = Grid::parse_input(input.as_bytes());
-    grid.bfs()
+pub fn p2(input: &str) -> Result<usize> {
+    Ok(lowest_total_risk(&enlarge(&parse_input(input)?, 5)))
 }
 
-pub fn p2(input: &str) -> usize {
-    let grid = Grid::parse_input(input.as_bytes());
-    grid.enlarge(5).bfs()
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = DAY;
+    type Input = Cave;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(cave: &Self::Input) -> Result<Answer> {
+        Ok(lowest_total_risk(cave).into())
+    }
+
+    fn part2(cave: &Self::Input) -> Result<Answer> {
+        Ok(lowest_total_risk(&enlarge(cave, 5)).into())
+    }
 }
 
 #[cfg(test)]
@@ -114,21 +80,33 @@ mod tests {
 
     #[test]
     fn test_example_p1() {
-        assert_eq!(p1(EXAMPLE_INPUT), EXAMPLE_P1_ANSWER);
+        assert_eq!(p1(EXAMPLE_INPUT), Ok(EXAMPLE_P1_ANSWER));
     }
 
     #[test]
     fn test_example_p2() {
-        assert_eq!(p2(EXAMPLE_INPUT), EXAMPLE_P2_ANSWER);
+        assert_eq!(p2(EXAMPLE_INPUT), Ok(EXAMPLE_P2_ANSWER));
     }
 
     #[test]
     fn test_problem_p1() {
-        assert_eq!(p1(PROBLEM_INPUT), PROBLEM_P1_ANSWER);
+        assert_eq!(p1(PROBLEM_INPUT), Ok(PROBLEM_P1_ANSWER));
     }
 
     #[test]
     fn test_problem_p2() {
-        assert_eq!(p2(PROBLEM_INPUT), PROBLEM_P2_ANSWER);
+        assert_eq!(p2(PROBLEM_INPUT), Ok(PROBLEM_P2_ANSWER));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("116\n138\n2x3\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 15, line 3, column 2: expected a risk level, found `x`"
+        );
+
+        let err = p1("116\n13\n").unwrap_err();
+        assert_eq!(err.reason, "expected 3 cells, found 2");
     }
 }
@@ -1,4 +1,21 @@
-use std::sync::Arc;
+use std::fmt::{self, Display};
+use std::iter::{Product, Sum};
+
+use crate::error::{Error, Result};
+use crate::solution::{Answer, Solution};
+
+use self::bigint::BigUint;
+use self::bits::BitReader;
+
+pub mod bigint;
+pub mod bits;
+pub mod compile;
+pub mod disasm;
+pub mod encode;
+pub mod stream;
+pub mod vm;
+
+const DAY: u8 = 16;
 
 // decoding bits rust
 // |---|---|
@@ -7,79 +24,646 @@ use std::sync::Arc;
 
 // Types
 // 4 = literal value
+// anything else = operator over the subpackets
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub version: u8,
+    pub kind: PacketType,
+}
 
-struct Packet {
-    version: usize,
-    t: PacketType,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacketType {
+    Literal(u64),
+    /// A literal too large for a `u64`.
+    WideLiteral(BigUint),
+    Operator(Operator, Vec<Packet>),
 }
 
-enum PacketType {
-    Literal(LiteralValue),
-    Operator(OperatorPacket),
-    Comparison(ComparisonPacket),
-    Null,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Sum,
+    Product,
+    Min,
+    Max,
+    Gt,
+    Lt,
+    Eq,
 }
 
-struct LiteralValue(i64);
+/// Type ID of literal value packets.
+pub const LITERAL: u8 = 4;
+
+/// How an operator packet says where its subpackets end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthType {
+    /// Type 0: the total length in bits of the subpackets, in 15 bits.
+    Bits,
+    /// Type 1: the number of subpackets, in 11 bits.
+    Count,
+}
+
+impl LengthType {
+    pub fn id(self) -> u8 {
+        match self {
+            LengthType::Bits => 0,
+            LengthType::Count => 1,
+        }
+    }
 
-impl LiteralValue {
-    pub fn parse_stream(stream: &mut impl Iterator<Item = char>) -> Self {
-        LiteralValue(0)
+    /// Width in bits of the length field that follows the length type ID.
+    pub fn field_width(self) -> usize {
+        match self {
+            LengthType::Bits => 15,
+            LengthType::Count => 11,
+        }
     }
 }
 
-struct OperatorPacket {
-    operator_init: i64,
-    operator_fn: fn(i64, i64) -> i64,
-    lhs: Arc<Packet>,
+impl Operator {
+    pub fn from_type_id(id: u8) -> Option<Self> {
+        Some(match id {
+            0 => Operator::Sum,
+            1 => Operator::Product,
+            2 => Operator::Min,
+            3 => Operator::Max,
+            5 => Operator::Gt,
+            6 => Operator::Lt,
+            7 => Operator::Eq,
+            _ => return None,
+        })
+    }
+
+    pub fn type_id(self) -> u8 {
+        match self {
+            Operator::Sum => 0,
+            Operator::Product => 1,
+            Operator::Min => 2,
+            Operator::Max => 3,
+            Operator::Gt => 5,
+            Operator::Lt => 6,
+            Operator::Eq => 7,
+        }
+    }
+
+    /// The name of the operator in expressions, see [`compile`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Operator::Sum => "sum",
+            Operator::Product => "product",
+            Operator::Min => "min",
+            Operator::Max => "max",
+            Operator::Gt => "gt",
+            Operator::Lt => "lt",
+            Operator::Eq => "eq",
+        }
+    }
+
+    pub fn is_comparison(self) -> bool {
+        matches!(self, Operator::Gt | Operator::Lt | Operator::Eq)
+    }
+
+    /// Comparisons take exactly two subpackets, the others at least one.
+    pub fn accepts(self, subpackets: usize) -> bool {
+        if self.is_comparison() {
+            subpackets == 2
+        } else {
+            subpackets > 0
+        }
+    }
+
+    /// Applies the operator to the values of its subpackets, which [`Operator::accepts`].
+    /// Sums and products of `u64`s can overflow, see [`Operator::checked_apply`].
+    pub fn apply<T>(self, values: &[T]) -> T
+    where
+        T: Clone + Ord + From<bool> + for<'a> Sum<&'a T> + for<'a> Product<&'a T>,
+    {
+        match self {
+            Operator::Sum => values.iter().sum(),
+            Operator::Product => values.iter().product(),
+            Operator::Min => values.iter().min().unwrap().clone(),
+            Operator::Max => values.iter().max().unwrap().clone(),
+            Operator::Gt => T::from(values[0] > values[1]),
+            Operator::Lt => T::from(values[0] < values[1]),
+            Operator::Eq => T::from(values[0] == values[1]),
+        }
+    }
+
+    /// Applies the operator, or `None` if the result does not fit in a `u64`.
+    pub fn checked_apply(self, values: &[u64]) -> Option<u64> {
+        match self {
+            Operator::Sum => values.iter().try_fold(0u64, |sum, &v| sum.checked_add(v)),
+            Operator::Product => values
+                .iter()
+                .try_fold(1u64, |product, &v| product.checked_mul(v)),
+            _ => Some(self.apply(values)),
+        }
+    }
 }
 
-impl OperatorPacket {
-    pub fn parse_stream(operator_init: i64, stream: &mut impl Iterator<Item = char>) -> Self {
-        OperatorPacket { operator_init,
-        match operator_init { 0=> ||,1=>,2=>,3=>,_=>} }
+impl Packet {
+    pub fn literal(version: u8, value: u64) -> Self {
+        Packet {
+            version,
+            kind: PacketType::Literal(value),
+        }
+    }
+
+    /// A literal packet, wide only if `value` does not fit in a `u64`.
+    pub fn big_literal(version: u8, value: BigUint) -> Self {
+        let kind = match value.to_u64() {
+            Some(value) => PacketType::Literal(value),
+            None => PacketType::WideLiteral(value),
+        };
+        Packet { version, kind }
+    }
+
+    pub fn operator(version: u8, operator: Operator, subpackets: Vec<Packet>) -> Self {
+        Packet {
+            version,
+            kind: PacketType::Operator(operator, subpackets),
+        }
+    }
+
+    pub fn type_id(&self) -> u8 {
+        match &self.kind {
+            PacketType::Literal(_) | PacketType::WideLiteral(_) => LITERAL,
+            PacketType::Operator(operator, _) => operator.type_id(),
+        }
     }
+
+    /// Sum of the versions of this packet and of all the packets it contains.
+    pub fn version_sum(&self) -> u64 {
+        let mut sum = 0;
+        let mut pending = vec![self];
+        while let Some(packet) = pending.pop() {
+            sum += packet.version as u64;
+            if let PacketType::Operator(_, subpackets) = &packet.kind {
+                pending.extend(subpackets);
+            }
+        }
+        sum
+    }
+
+    /// Folds the tree bottom up without recursing, so deeply nested trees cannot
+    /// overflow the stack. `visit` gets each packet after its subpackets, with its
+    /// position in pre-order and what it gave for each subpacket, and the first error
+    /// it returns stops the fold.
+    fn fold<T, E>(
+        &self,
+        mut visit: impl FnMut(&Packet, usize, Vec<T>) -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        // a packet is numbered when first seen and visited once its subpackets have been
+        let mut pending: Vec<(&Packet, Option<usize>)> = vec![(self, None)];
+        let mut values: Vec<T> = Vec::new();
+        let mut position = 0;
+
+        while let Some((packet, seen)) = pending.pop() {
+            let value = match (&packet.kind, seen) {
+                (PacketType::Operator(_, subpackets), None) => {
+                    pending.push((packet, Some(position)));
+                    pending.extend(subpackets.iter().rev().map(|subpacket| (subpacket, None)));
+                    position += 1;
+                    continue;
+                }
+                (PacketType::Operator(_, subpackets), Some(seen)) => {
+                    let operands = values.split_off(values.len() - subpackets.len());
+                    visit(packet, seen, operands)?
+                }
+                (_, _) => {
+                    position += 1;
+                    visit(packet, position - 1, Vec::new())?
+                }
+            };
+            values.push(value);
+        }
+        Ok(values.pop().unwrap())
+    }
+
+    /// The value of the expression this packet represents, or the first packet to be
+    /// evaluated whose value does not fit in a `u64`.
+    pub fn checked_value(&self) -> std::result::Result<u64, Overflow> {
+        self.fold(|packet, position, values| match &packet.kind {
+            PacketType::Literal(value) => Ok(*value),
+            PacketType::WideLiteral(_) => Err(Overflow {
+                packet: position,
+                operator: None,
+            }),
+            PacketType::Operator(operator, _) => operator.checked_apply(&values).ok_or(Overflow {
+                packet: position,
+                operator: Some(*operator),
+            }),
+        })
+    }
+
+    /// The exact value of the expression this packet represents, however large.
+    pub fn big_value(&self) -> BigUint {
+        let value = self.fold(|packet, _, values| {
+            Ok::<_, std::convert::Infallible>(match &packet.kind {
+                PacketType::Literal(value) => BigUint::from(*value),
+                PacketType::WideLiteral(value) => value.clone(),
+                PacketType::Operator(operator, _) => operator.apply(&values),
+            })
+        });
+        match value {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+}
+
+/// Drops the subpackets from a list rather than recursively.
+impl Drop for Packet {
+    fn drop(&mut self) {
+        if let PacketType::Operator(_, subpackets) = &mut self.kind {
+            let mut pending = std::mem::take(subpackets);
+            while let Some(mut packet) = pending.pop() {
+                if let PacketType::Operator(_, subpackets) = &mut packet.kind {
+                    pending.append(subpackets);
+                }
+            }
+        }
+    }
+}
+
+/// A packet whose value does not fit in a `u64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+    /// Position of the packet in the tree, in pre-order, which indexes its [`Layout`].
+    pub packet: usize,
+    /// The operator of the packet, `None` for a literal.
+    pub operator: Option<Operator>,
 }
 
-struct ComparisonPacket {
-    comparison_fn: fn(i64, i64) -> i64,
-    lhs: Arc<Packet>,
-    rhs: Arc<Packet>,
+impl Overflow {
+    /// The crate error for this overflow, naming the bit offset of the packet.
+    pub fn to_error(self, layouts: &[Layout]) -> Error {
+        let what = match self.operator {
+            Some(operator) => format!("the {:?} packet", operator),
+            None => "the literal".to_string(),
+        };
+        Error::new(
+            DAY,
+            format!(
+                "bit {}: {} does not fit in 64 bits",
+                layouts[self.packet].offset, what
+            ),
+        )
+    }
 }
 
-impl ComparisonPacket {
-    pub fn parse_stream(stream: &mut impl Iterator<Item = char>) -> Self {
-        ComparisonPacket {}
+/// Why a transmission could not be decoded, with the bit it was decoded up to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// `found` is the `index`-th character of the transmission, and not a hex digit.
+    InvalidHex {
+        index: usize,
+        found: char,
+    },
+    UnexpectedEnd {
+        offset: usize,
+    },
+    /// A comparison packet at `offset` without exactly two subpackets.
+    Arity {
+        offset: usize,
+        operator: Operator,
+        subpackets: usize,
+    },
+    /// A packet at `offset` that is not a comparison and has no subpackets.
+    EmptyOperator {
+        offset: usize,
+        operator: Operator,
+    },
+    /// The first set bit after the outermost packet.
+    TrailingBits {
+        offset: usize,
+    },
+}
+
+impl DecodeError {
+    /// Bit offset of the problem from the start of the transmission.
+    pub fn offset(&self) -> usize {
+        match *self {
+            DecodeError::InvalidHex { index, .. } => index * 4,
+            DecodeError::UnexpectedEnd { offset }
+            | DecodeError::Arity { offset, .. }
+            | DecodeError::EmptyOperator { offset, .. }
+            | DecodeError::TrailingBits { offset } => offset,
+        }
+    }
+
+    pub fn reason(&self) -> String {
+        match self {
+            DecodeError::InvalidHex { found, .. } => format!("invalid hex character `{}`", found),
+            DecodeError::UnexpectedEnd { .. } => {
+                "the transmission ends in the middle of a packet".to_string()
+            }
+            DecodeError::Arity {
+                operator,
+                subpackets,
+                ..
+            } => format!("{:?} cannot apply to {} subpackets", operator, subpackets),
+            DecodeError::EmptyOperator { operator, .. } => {
+                format!("{:?} has no subpackets", operator)
+            }
+            DecodeError::TrailingBits { .. } => {
+                "the transmission has set bits after its outermost packet".to_string()
+            }
+        }
+    }
+
+    /// The crate error for this problem in `input`, the text the transmission was decoded
+    /// from. Invalid hex is pointed at in the text, anything else by its bit offset.
+    pub fn to_error(&self, input: &str) -> Error {
+        match *self {
+            DecodeError::InvalidHex { index, .. } => {
+                let hex = input.trim();
+                Error::at(DAY, input, &hex[index..], self.reason())
+            }
+            _ => Error::new(DAY, self.to_string()),
+        }
     }
 }
 
-fn parse_stream(stream: &mut impl Iterator<Item = char>) -> Packet {
-    let version: usize = 0;
-    let id: i64 = 0;
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bit {}: {}", self.offset(), self.reason())
+    }
+}
 
-    let t = match id {
-        0 | 1 | 2 | 3 => PacketType::Operator(OperatorPacket::parse_stream(id, stream)),
-        4 => PacketType::Literal(LiteralValue::parse_stream(stream)),
-        5 | 6 | 7 => PacketType::Comparison(ComparisonPacket::parse_stream(stream)),
-        _ => unreachable!(),
-    };
+impl std::error::Error for DecodeError {}
 
-    Packet { version, t }
+type Decoded<T> = std::result::Result<T, DecodeError>;
+
+/// Decodes hex digits, in either case, into bytes, four bits per digit. An odd digit count
+/// leaves the last byte half filled, so the number of bits is returned alongside.
+fn hex_to_bytes(hex: &str) -> Decoded<(Vec<u8>, usize)> {
+    let nibbles = hex
+        .char_indices()
+        .map(|(index, found)| match found.to_digit(16) {
+            Some(digit) => Ok(digit as u8),
+            None => Err(DecodeError::InvalidHex { index, found }),
+        })
+        .collect::<Decoded<Vec<u8>>>()?;
+    let bytes = nibbles
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).unwrap_or(&0))
+        .collect();
+    Ok((bytes, nibbles.len() * 4))
 }
 
-pub fn p1(input: &str) -> Result<usize, ()> {
-    Ok(0)
+/// Reads the next `n` bits, failing if the transmission ends first.
+fn take(reader: &mut BitReader, n: usize) -> Decoded<u64> {
+    let offset = reader.position();
+    reader
+        .read_bits(n)
+        .ok_or(DecodeError::UnexpectedEnd { offset })
 }
 
-pub fn p2(input: &str) -> Result<usize, ()> {
-    Ok(0)
+/// Where a packet sits in its transmission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    /// Bit offset of the packet header from the start of the transmission.
+    pub offset: usize,
+    /// Length in bits of the packet, subpackets included.
+    pub length: usize,
+    /// How the packet delimits its subpackets, if it is an operator.
+    pub length_type: Option<LengthType>,
+}
+
+/// An operator packet whose subpackets are being parsed.
+struct Pending<'a> {
+    /// Index of the packet's layout.
+    index: usize,
+    version: u8,
+    operator: Operator,
+    length_type: LengthType,
+    /// Reads the subpackets: only them for length type 0, else onwards from the header.
+    reader: BitReader<'a>,
+    /// How many subpackets the header announces, for length type 1.
+    count: u64,
+    subpackets: Vec<Packet>,
+}
+
+impl Pending<'_> {
+    fn is_complete(&self) -> bool {
+        match self.length_type {
+            LengthType::Bits => self.reader.is_empty(),
+            LengthType::Count => self.subpackets.len() as u64 == self.count,
+        }
+    }
+
+    fn finish(self, layouts: &mut [Layout]) -> Decoded<Packet> {
+        let layout = &mut layouts[self.index];
+        let (offset, operator) = (layout.offset, self.operator);
+        if self.subpackets.is_empty() && !operator.is_comparison() {
+            return Err(DecodeError::EmptyOperator { offset, operator });
+        }
+        if !operator.accepts(self.subpackets.len()) {
+            return Err(DecodeError::Arity {
+                offset,
+                operator,
+                subpackets: self.subpackets.len(),
+            });
+        }
+        layout.length = self.reader.position() - offset;
+        layout.length_type = Some(self.length_type);
+        Ok(Packet::operator(self.version, operator, self.subpackets))
+    }
+}
+
+/// Parses the next packet, adding its layout and those of its subpackets to `layouts`
+/// in pre-order.
+///
+/// Operators whose subpackets are still being parsed are kept on a stack of their own
+/// rather than the call stack, so deeply nested transmissions decode too.
+fn parse_packet<'a>(reader: &mut BitReader<'a>, layouts: &mut Vec<Layout>) -> Decoded<Packet> {
+    let mut stack: Vec<Pending<'a>> = Vec::new();
+    loop {
+        // the innermost operator's reader, which a length type 1 operator hands back to
+        // the one around it once complete
+        let active = match stack.last_mut() {
+            Some(pending) => &mut pending.reader,
+            None => &mut *reader,
+        };
+        let index = layouts.len();
+        let offset = active.position();
+        layouts.push(Layout {
+            offset,
+            length: 0,
+            length_type: None,
+        });
+
+        let version = take(active, 3)? as u8;
+        let id = take(active, 3)? as u8;
+        let mut packet = match Operator::from_type_id(id) {
+            None => {
+                let kind = parse_literal(active)?;
+                layouts[index].length = active.position() - offset;
+                Some(Packet { version, kind })
+            }
+            Some(operator) => {
+                let (length_type, reader, count) = parse_length(active)?;
+                stack.push(Pending {
+                    index,
+                    version,
+                    operator,
+                    length_type,
+                    reader,
+                    count,
+                    subpackets: Vec::new(),
+                });
+                None
+            }
+        };
+
+        // hand the packet to its operator, completing every operator it completes
+        loop {
+            let pending = match stack.last_mut() {
+                Some(pending) => pending,
+                None => return Ok(packet.unwrap()),
+            };
+            pending.subpackets.extend(packet.take());
+            if !pending.is_complete() {
+                break;
+            }
+            let pending = stack.pop().unwrap();
+            if pending.length_type == LengthType::Count {
+                match stack.last_mut() {
+                    Some(outer) => outer.reader = pending.reader.clone(),
+                    None => *reader = pending.reader.clone(),
+                }
+            }
+            packet = Some(pending.finish(layouts)?);
+        }
+    }
+}
+
+/// Reads how an operator delimits its subpackets, returning the reader to parse them
+/// with and, for length type 1, how many there are.
+fn parse_length<'a>(reader: &mut BitReader<'a>) -> Decoded<(LengthType, BitReader<'a>, u64)> {
+    if take(reader, 1)? == LengthType::Bits.id() as u64 {
+        let total_length = take(reader, LengthType::Bits.field_width())? as usize;
+        let offset = reader.position();
+        let contents = reader
+            .sub_reader(total_length)
+            .ok_or(DecodeError::UnexpectedEnd { offset })?;
+        Ok((LengthType::Bits, contents, 0))
+    } else {
+        let count = take(reader, LengthType::Count.field_width())?;
+        Ok((LengthType::Count, reader.clone(), count))
+    }
+}
+
+/// Reads the groups of a literal value, switching to a [`BigUint`] once the value
+/// outgrows a `u64`.
+fn parse_literal(reader: &mut BitReader) -> Decoded<PacketType> {
+    let mut value: u64 = 0;
+    let mut wide: Option<BigUint> = None;
+    loop {
+        // each group is a continuation bit followed by 4 bits of the value
+        let group = take(reader, 5)?;
+        let nibble = (group & 0xF) as u8;
+        match &mut wide {
+            Some(big) => big.push_nibble(nibble),
+            None if value >> 60 != 0 => {
+                let mut big = BigUint::from(value);
+                big.push_nibble(nibble);
+                wide = Some(big);
+            }
+            None => value = value << 4 | nibble as u64,
+        }
+        if group >> 4 == 0 {
+            return Ok(match wide {
+                Some(big) => PacketType::WideLiteral(big),
+                None => PacketType::Literal(value),
+            });
+        }
+    }
+}
+
+/// Checks that only zeros, the padding to whole hex digits, follow the outermost packet.
+fn check_padding(reader: &mut BitReader) -> Decoded<()> {
+    while !reader.is_empty() {
+        let offset = reader.position();
+        let n = reader.remaining().min(64);
+        let bits = reader.read_bits(n).unwrap();
+        if bits != 0 {
+            let first_set = bits.leading_zeros() as usize - (64 - n);
+            return Err(DecodeError::TrailingBits {
+                offset: offset + first_set,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a hex transmission.
+pub fn decode(hex: &str) -> Decoded<Packet> {
+    Ok(decode_with_layout(hex)?.0)
+}
+
+/// Decodes a hex transmission along with the layout of each of its packets, in pre-order.
+pub fn decode_with_layout(hex: &str) -> Decoded<(Packet, Vec<Layout>)> {
+    let (bytes, len) = hex_to_bytes(hex.trim())?;
+    let mut reader = BitReader::with_len(&bytes, len);
+    let mut layouts = Vec::new();
+    let packet = parse_packet(&mut reader, &mut layouts)?;
+    check_padding(&mut reader)?;
+    Ok((packet, layouts))
+}
+
+pub fn parse_transmission(input: &str) -> Result<Packet> {
+    decode(input).map_err(|err| err.to_error(input))
+}
+
+pub fn parse_with_layout(input: &str) -> Result<(Packet, Vec<Layout>)> {
+    decode_with_layout(input).map_err(|err| err.to_error(input))
+}
+
+pub fn p1(input: &str) -> Result<u64> {
+    Ok(parse_transmission(input)?.version_sum())
+}
+
+pub fn p2(input: &str) -> Result<u64> {
+    let (packet, layouts) = parse_with_layout(input)?;
+    packet
+        .checked_value()
+        .map_err(|overflow| overflow.to_error(&layouts))
+}
+
+/// Part 2 evaluated exactly, for transmissions whose values do not fit in a `u64`.
+pub fn p2_big(input: &str) -> Result<BigUint> {
+    Ok(parse_transmission(input)?.big_value())
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = DAY;
+    type Input = (Packet, Vec<Layout>);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_with_layout(input)
+    }
+
+    fn part1((packet, _): &Self::Input) -> Result<Answer> {
+        Ok(packet.version_sum().into())
+    }
+
+    fn part2((packet, layouts): &Self::Input) -> Result<Answer> {
+        let value = packet
+            .checked_value()
+            .map_err(|overflow| overflow.to_error(layouts))?;
+        Ok(value.into())
+    }
 }
 
 #[cfg(test)]
 mod p1_tests {
     use super::*;
 
-    const PROBLEM_INPUT: &str = "E20D41802B2984BD00540010F82D09E35880350D61A41D3004E5611E585F40159ED7AD7C90CF6BD6BE49C802DEB00525272CC1927752698693DA7C70029C0081002140096028C5400F6023C9C00D601ED88070070030005C2201448400E400F40400C400A50801E20004C1000809D14700B67676EE661137ADC64FF2BBAD745B3F2D69026335E92A0053533D78932A9DFE23AC7858C028920A973785338832CFA200F47C81D2BBBC7F9A9E1802FE00ACBA44F4D1E775DDC19C8054D93B7E72DBE7006AA200C41A8510980010D8731720CB80132918319804738AB3A8D3E773C4A4015A498E680292B1852E753E2B29D97F0DE6008CB3D4D031802D2853400D24DEAE0137AB8210051D24EB600844B95C56781B3004F002B99D8F635379EDE273AF26972D4A5610BA51004C12D1E25D802F32313239377B37100105343327E8031802B801AA00021D07231C2F10076184668693AC6600BCD83E8025231D752E5ADE311008A4EA092754596C6789727F069F99A4645008247D2579388DCF53558AE4B76B257200AAB80107947E94789FE76E36402868803F0D62743F00043A1646288800084C3F8971308032996A2BD8023292DF8BE467BB3790047F2572EF004A699E6164C013A007C62848DE91CC6DB459B6B40087E530AB31EE633BD23180393CBF36333038E011CBCE73C6FB098F4956112C98864EA1C2801D2D0F319802D60088002190620E479100622E4358952D84510074C0188CF0923410021F1CE1146E3006E3FC578EE600A4B6C4B002449C97E92449C97E92459796EB4FF874400A9A16100A26CEA6D0E5E5EC8841C9B8FE37109C99818023A00A4FD8BA531586BB8B1DC9AE080293B6972B7FA444285CC00AE492BC910C1697B5BDD8425409700562F471201186C0120004322B42489A200D4138A71AA796D00374978FE07B2314E99BFB6E909678A0";
+    const PROBLEM_INPUT: &str = include_str!("inputs/d16.txt");
 
     macro_rules! p1_tests {
         ($($name:ident: $value:expr,)*) => {
@@ -98,7 +682,7 @@ mod p1_tests {
         p1_1: ("620080001611562C8802118E34", Ok(12)),
         p1_2: ("C0015000016115A2E0802F182340", Ok(23)),
         p1_3: ("A0016C880162017C3686B18A3D4780", Ok(31)),
-        p1_problem: (PROBLEM_INPUT, Ok(31)),
+        p1_problem: (PROBLEM_INPUT, Ok(984)),
     }
 }
 
@@ -106,7 +690,7 @@ mod p1_tests {
 mod p2_tests {
     use super::*;
 
-    const PROBLEM_INPUT: &str = "E20D41802B2984BD00540010F82D09E35880350D61A41D3004E5611E585F40159ED7AD7C90CF6BD6BE49C802DEB00525272CC1927752698693DA7C70029C0081002140096028C5400F6023C9C00D601ED88070070030005C2201448400E400F40400C400A50801E20004C1000809D14700B67676EE661137ADC64FF2BBAD745B3F2D69026335E92A0053533D78932A9DFE23AC7858C028920A973785338832CFA200F47C81D2BBBC7F9A9E1802FE00ACBA44F4D1E775DDC19C8054D93B7E72DBE7006AA200C41A8510980010D8731720CB80132918319804738AB3A8D3E773C4A4015A498E680292B1852E753E2B29D97F0DE6008CB3D4D031802D2853400D24DEAE0137AB8210051D24EB600844B95C56781B3004F002B99D8F635379EDE273AF26972D4A5610BA51004C12D1E25D802F32313239377B37100105343327E8031802B801AA00021D07231C2F10076184668693AC6600BCD83E8025231D752E5ADE311008A4EA092754596C6789727F069F99A4645008247D2579388DCF53558AE4B76B257200AAB80107947E94789FE76E36402868803F0D62743F00043A1646288800084C3F8971308032996A2BD8023292DF8BE467BB3790047F2572EF004A699E6164C013A007C62848DE91CC6DB459B6B40087E530AB31EE633BD23180393CBF36333038E011CBCE73C6FB098F4956112C98864EA1C2801D2D0F319802D60088002190620E479100622E4358952D84510074C0188CF0923410021F1CE1146E3006E3FC578EE600A4B6C4B002449C97E92449C97E92459796EB4FF874400A9A16100A26CEA6D0E5E5EC8841C9B8FE37109C99818023A00A4FD8BA531586BB8B1DC9AE080293B6972B7FA444285CC00AE492BC910C1697B5BDD8425409700562F471201186C0120004322B42489A200D4138A71AA796D00374978FE07B2314E99BFB6E909678A0";
+    const PROBLEM_INPUT: &str = include_str!("inputs/d16.txt");
 
     macro_rules! p2_tests {
         ($($name:ident: $value:expr,)*) => {
@@ -121,10 +705,141 @@ mod p2_tests {
     }
 
     p2_tests! {
-        p1_0: ("8A004A801A8002F478", Ok(16)),
-        p1_1: ("620080001611562C8802118E34", Ok(12)),
-        p1_2: ("C0015000016115A2E0802F182340", Ok(23)),
-        p1_3: ("A0016C880162017C3686B18A3D4780", Ok(31)),
-        p1_problem: (PROBLEM_INPUT, Ok(31)),
+        p2_sum: ("C200B40A82", Ok(3)),
+        p2_product: ("04005AC33890", Ok(54)),
+        p2_min: ("880086C3E88112", Ok(7)),
+        p2_max: ("CE00C43D881120", Ok(9)),
+        p2_lt: ("D8005AC2A8F0", Ok(1)),
+        p2_gt: ("F600BC2D8F", Ok(0)),
+        p2_eq: ("9C005AC2F8F0", Ok(0)),
+        p2_nested: ("9C0141080250320F1802104A08", Ok(1)),
+        p2_problem: (PROBLEM_INPUT, Ok(1015320896946)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_hex() {
+        let input = "0123456789ABCDEF";
+        assert_eq!(
+            hex_to_bytes(input),
+            Ok((vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF], 64))
+        );
+        assert_eq!(hex_to_bytes("D2F"), Ok((vec![0xD2, 0xF0], 12)));
+        assert_eq!(hex_to_bytes("abcdef"), hex_to_bytes("ABCDEF"));
+        assert_eq!(decode("d2fe28"), Ok(Packet::literal(6, 2021)));
+    }
+
+    #[test]
+    fn test_parse_packets() {
+        assert_eq!(parse_transmission("D2FE28"), Ok(Packet::literal(6, 2021)));
+        assert_eq!(
+            parse_transmission("38006F45291200"),
+            Ok(Packet::operator(
+                1,
+                Operator::Lt,
+                vec![Packet::literal(6, 10), Packet::literal(2, 20)]
+            ))
+        );
+        assert_eq!(
+            parse_transmission("EE00D40C823060"),
+            Ok(Packet::operator(
+                7,
+                Operator::Max,
+                vec![
+                    Packet::literal(2, 1),
+                    Packet::literal(4, 2),
+                    Packet::literal(1, 3)
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("8A004G801A8002F478").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 16, line 1, column 6: invalid hex character `G`"
+        );
+
+        let err = p1("8A004A801A80").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 16: bit 43: the transmission ends in the middle of a packet"
+        );
+
+        // a less than packet over three literals
+        let err = p1("DA00C40882106").unwrap_err();
+        assert_eq!(err.reason, "bit 0: Lt cannot apply to 3 subpackets");
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode("8A004G801A8002F478"),
+            Err(DecodeError::InvalidHex {
+                index: 5,
+                found: 'G'
+            })
+        );
+        assert_eq!(
+            decode("8A004A801A80"),
+            Err(DecodeError::UnexpectedEnd { offset: 43 })
+        );
+        assert_eq!(
+            decode("DA00C40882106"),
+            Err(DecodeError::Arity {
+                offset: 0,
+                operator: Operator::Lt,
+                subpackets: 3
+            })
+        );
+        // a sum counting zero subpackets
+        assert_eq!(
+            decode("020000"),
+            Err(DecodeError::EmptyOperator {
+                offset: 0,
+                operator: Operator::Sum
+            })
+        );
+        // D2FE28 with the last padding bit set
+        assert_eq!(
+            decode("D2FE29"),
+            Err(DecodeError::TrailingBits { offset: 23 })
+        );
+    }
+
+    #[test]
+    fn test_wide_values() {
+        // seventeen groups of 4 bits
+        let wide = decode("13FFFFFFFFFFFFFFFFFFFDE0").unwrap();
+        assert_eq!(wide.big_value().to_string(), "295147905179352825855");
+        assert_eq!(
+            wide.checked_value(),
+            Err(Overflow {
+                packet: 0,
+                operator: None
+            })
+        );
+
+        // the product of 2^32 and 2^32 + 1, under a sum
+        let hex = "020041802123084210840024610842108040";
+        let product = decode(hex).unwrap();
+        assert_eq!(product.big_value().to_string(), "18446744078004518912");
+        assert_eq!(
+            product.checked_value(),
+            Err(Overflow {
+                packet: 1,
+                operator: Some(Operator::Product)
+            })
+        );
+        assert_eq!(
+            p2(hex).unwrap_err().reason,
+            "bit 18: the Product packet does not fit in 64 bits"
+        );
     }
 }
@@ -0,0 +1,269 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::iter::{Product, Sum};
+use std::ops::{Add, Mul};
+
+use serde::{Serialize, Serializer};
+
+/// An unsigned integer of any size, for values too large for a `u64`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    /// Base 2^32 digits, least significant first, without trailing zeros.
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs[..] {
+            [] => Some(0),
+            [low] => Some(low as u64),
+            [low, high] => Some((high as u64) << 32 | low as u64),
+            _ => None,
+        }
+    }
+
+    /// Parses a string of decimal digits.
+    pub fn from_decimal(digits: &str) -> Option<Self> {
+        if digits.is_empty() {
+            return None;
+        }
+        digits.chars().try_fold(BigUint::zero(), |mut n, c| {
+            n.mul_add_small(10, c.to_digit(10)?);
+            Some(n)
+        })
+    }
+
+    /// Appends a hex digit: `self * 16 + nibble`.
+    pub fn push_nibble(&mut self, nibble: u8) {
+        self.mul_add_small(16, nibble as u32);
+    }
+
+    /// The hex digits of the number, most significant first, at least one.
+    pub fn nibbles(&self) -> Vec<u8> {
+        let mut nibbles: Vec<u8> = self
+            .limbs
+            .iter()
+            .rev()
+            .flat_map(|limb| (0..8).rev().map(move |i| (limb >> (i * 4)) as u8 & 0xF))
+            .skip_while(|&nibble| nibble == 0)
+            .collect();
+        if nibbles.is_empty() {
+            nibbles.push(0);
+        }
+        nibbles
+    }
+
+    fn mul_add_small(&mut self, factor: u32, addend: u32) {
+        let mut carry = addend as u64;
+        for limb in &mut self.limbs {
+            let n = *limb as u64 * factor as u64 + carry;
+            *limb = n as u32;
+            carry = n >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+        self.normalize();
+    }
+
+    /// Divides in place, returning the remainder.
+    fn div_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let n = remainder << 32 | *limb as u64;
+            *limb = (n / divisor as u64) as u32;
+            remainder = n % divisor as u64;
+        }
+        self.normalize();
+        remainder as u32
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        let mut big = BigUint {
+            limbs: vec![n as u32, (n >> 32) as u32],
+        };
+        big.normalize();
+        big
+    }
+}
+
+impl From<bool> for BigUint {
+    fn from(b: bool) -> Self {
+        BigUint::from(b as u64)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = 0u64;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let n = *self.limbs.get(i).unwrap_or(&0) as u64
+                + *other.limbs.get(i).unwrap_or(&0) as u64
+                + carry;
+            limbs.push(n as u32);
+            carry = n >> 32;
+        }
+        limbs.push(carry as u32);
+        let mut sum = BigUint { limbs };
+        sum.normalize();
+        sum
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let n = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = n as u32;
+                carry = n >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        let mut product = BigUint { limbs };
+        product.normalize();
+        product
+    }
+}
+
+impl<'a> Sum<&'a BigUint> for BigUint {
+    fn sum<I: Iterator<Item = &'a BigUint>>(iter: I) -> Self {
+        iter.fold(BigUint::zero(), |sum, n| &sum + n)
+    }
+}
+
+impl<'a> Product<&'a BigUint> for BigUint {
+    fn product<I: Iterator<Item = &'a BigUint>>(iter: I) -> Self {
+        iter.fold(BigUint::from(1), |product, n| &product * n)
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // peel off 9 decimal digits at a time, least significant first
+        let mut n = self.clone();
+        let mut chunks = Vec::new();
+        loop {
+            chunks.push(n.div_small(1_000_000_000));
+            if n.is_zero() {
+                break;
+            }
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        chunks
+            .iter()
+            .rev()
+            .try_for_each(|chunk| write!(f, "{:09}", chunk))
+    }
+}
+
+/// A JSON number when it fits in a `u64`, a string of digits otherwise.
+impl Serialize for BigUint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.to_u64() {
+            Some(n) => serializer.serialize_u64(n),
+            None => serializer.collect_str(self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(digits: &str) -> BigUint {
+        BigUint::from_decimal(digits).unwrap()
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let max = BigUint::from(u64::MAX);
+        assert_eq!(
+            (&max + &BigUint::from(1)).to_string(),
+            "18446744073709551616"
+        );
+        assert_eq!(
+            (&max * &max).to_string(),
+            "340282366920938463426481119284349108225"
+        );
+        assert_eq!(
+            &big("1000000000000000000000") * &BigUint::zero(),
+            BigUint::zero()
+        );
+        assert_eq!(BigUint::zero().to_string(), "0");
+
+        let values = [big("12345678901234567890123"), BigUint::from(7)];
+        assert_eq!(
+            values.iter().sum::<BigUint>(),
+            big("12345678901234567890130")
+        );
+        assert_eq!(
+            values.iter().product::<BigUint>(),
+            big("86419752308641975230861")
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(big("18446744073709551615").to_u64(), Some(u64::MAX));
+        assert_eq!(big("18446744073709551616").to_u64(), None);
+        assert_eq!(BigUint::from_decimal("12a"), None);
+
+        let mut n = BigUint::zero();
+        for nibble in [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xF].iter() {
+            n.push_nibble(*nibble);
+        }
+        assert_eq!(n.to_string(), "18446744073709551631");
+        assert_eq!(n.nibbles()[..3], [1, 0, 0]);
+        assert_eq!(n.nibbles().len(), 17);
+        assert_eq!(BigUint::zero().nibbles(), [0]);
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(big("18446744073709551616") > BigUint::from(u64::MAX));
+        assert!(BigUint::from(3) < BigUint::from(5));
+        assert_eq!(
+            big("100000000000000000000").cmp(&big("100000000000000000000")),
+            Ordering::Equal
+        );
+    }
+}
@@ -0,0 +1,279 @@
+/// Reads a bit string most significant bit first, without copying it.
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// Index of the next bit to read, counted from the start of `bytes`.
+    position: usize,
+    /// Index one past the last bit this reader may read.
+    end: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Reads every bit of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        BitReader::with_len(bytes, bytes.len() * 8)
+    }
+
+    /// Reads the first `len` bits of `bytes`.
+    pub fn with_len(bytes: &'a [u8], len: usize) -> Self {
+        assert!(
+            len <= bytes.len() * 8,
+            "{} bits do not fit in {} bytes",
+            len,
+            bytes.len()
+        );
+        BitReader {
+            bytes,
+            position: 0,
+            end: len,
+        }
+    }
+
+    /// How many bits have been read, counted from the start of the underlying bytes, so
+    /// positions in a sub-reader line up with those in the reader it came from.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.end - self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Reads the next `n` bits (at most 64) as a number, or `None` if fewer are left.
+    pub fn read_bits(&mut self, n: usize) -> Option<u64> {
+        assert!(n <= 64, "cannot read {} bits into a u64", n);
+        if n > self.remaining() {
+            return None;
+        }
+
+        let mut value = 0u64;
+        let mut left = n;
+        while left > 0 {
+            let byte = self.bytes[self.position / 8];
+            let available = 8 - self.position % 8;
+            let taken = available.min(left);
+            let bits = (byte >> (available - taken)) as u64 & ((1 << taken) - 1);
+            value = value << taken | bits;
+            self.position += taken;
+            left -= taken;
+        }
+        Some(value)
+    }
+
+    pub fn read_bit(&mut self) -> Option<bool> {
+        self.read_bits(1).map(|bit| bit == 1)
+    }
+
+    /// Splits off the next `len` bits as their own reader, skipping them in this one.
+    pub fn sub_reader(&mut self, len: usize) -> Option<BitReader<'a>> {
+        if len > self.remaining() {
+            return None;
+        }
+        let sub = BitReader {
+            bytes: self.bytes,
+            position: self.position,
+            end: self.position + len,
+        };
+        self.position += len;
+        Some(sub)
+    }
+}
+
+/// Builds a bit string most significant bit first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        BitWriter::default()
+    }
+
+    /// How many bits have been written.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Writes the low `n` bits (at most 64) of `value`.
+    pub fn write_bits(&mut self, value: u64, n: usize) {
+        assert!(n <= 64, "cannot write {} bits from a u64", n);
+        assert!(
+            n == 64 || value >> n == 0,
+            "{} does not fit in {} bits",
+            value,
+            n
+        );
+
+        let mut left = n;
+        while left > 0 {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            let free = 8 - self.len % 8;
+            let written = free.min(left);
+            let bits = (value >> (left - written)) & ((1 << written) - 1);
+            *self.bytes.last_mut().unwrap() |= (bits << (free - written)) as u8;
+            self.len += written;
+            left -= written;
+        }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        self.write_bits(bit as u64, 1);
+    }
+
+    /// Overwrites the `n` bits (at most 64) written from bit `position` on with the low `n`
+    /// bits of `value`, to fill in a field reserved before what it describes was written.
+    pub fn set_bits(&mut self, position: usize, value: u64, n: usize) {
+        assert!(n <= 64, "cannot write {} bits from a u64", n);
+        assert!(
+            n == 64 || value >> n == 0,
+            "{} does not fit in {} bits",
+            value,
+            n
+        );
+        assert!(
+            position + n <= self.len,
+            "bits {}..{} have not been written",
+            position,
+            position + n
+        );
+
+        let mut position = position;
+        let mut left = n;
+        while left > 0 {
+            let free = 8 - position % 8;
+            let written = free.min(left);
+            let mask = (((1u16 << written) - 1) << (free - written)) as u8;
+            let bits = ((value >> (left - written)) << (free - written)) as u8;
+            let byte = &mut self.bytes[position / 8];
+            *byte = *byte & !mask | bits;
+            position += written;
+            left -= written;
+        }
+    }
+
+    /// Writes every bit of `other` after the bits already written.
+    pub fn append(&mut self, other: &BitWriter) {
+        let mut reader = other.reader();
+        while !reader.is_empty() {
+            let n = reader.remaining().min(64);
+            self.write_bits(reader.read_bits(n).unwrap(), n);
+        }
+    }
+
+    /// A reader over the bits written so far.
+    pub fn reader(&self) -> BitReader<'_> {
+        BitReader::with_len(&self.bytes, self.len)
+    }
+
+    /// The bytes written, the last one padded with zeros, and the number of bits.
+    pub fn into_bytes(self) -> (Vec<u8>, usize) {
+        (self.bytes, self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_bits() {
+        // 1101 0010 1111 1110 0010 1000
+        let mut reader = BitReader::new(&[0xD2, 0xFE, 0x28]);
+        assert_eq!(reader.read_bits(3), Some(6));
+        assert_eq!(reader.read_bits(3), Some(4));
+        assert_eq!(reader.read_bits(5), Some(0b10111));
+        assert_eq!(reader.read_bits(10), Some(0b1111000101));
+        assert_eq!(reader.position(), 21);
+        assert_eq!(reader.read_bits(4), None);
+        assert_eq!(reader.read_bits(3), Some(0));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_read_wide() {
+        let bytes = [0xFF, 0, 0, 0, 0, 0, 0, 0x01, 0x80];
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read_bits(64), Some(0xFF00_0000_0000_0001));
+        assert_eq!(reader.read_bit(), Some(true));
+
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read_bits(4), Some(0xF));
+        assert_eq!(reader.read_bits(64), Some(0xF000_0000_0000_0018));
+    }
+
+    #[test]
+    fn test_sub_reader() {
+        let mut reader = BitReader::with_len(&[0b1010_1100, 0b0110_0000], 12);
+        reader.read_bits(2);
+        let mut sub = reader.sub_reader(4).unwrap();
+        assert_eq!(reader.position(), 6);
+        assert_eq!(sub.position(), 2);
+        assert_eq!(sub.read_bits(4), Some(0b1011));
+        assert_eq!(sub.read_bit(), None);
+
+        assert!(reader.sub_reader(7).is_none());
+        assert_eq!(reader.read_bits(6), Some(0b000110));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_write_bits() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(6, 3);
+        writer.write_bits(4, 3);
+        writer.write_bits(0b10111, 5);
+        writer.write_bits(0b1111000101, 10);
+        writer.write_bits(0, 3);
+        assert_eq!(writer.len(), 24);
+        assert_eq!(writer.into_bytes(), (vec![0xD2, 0xFE, 0x28], 24));
+
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        writer.write_bits(u64::MAX, 64);
+        let mut reader = writer.reader();
+        assert_eq!(reader.read_bit(), Some(true));
+        assert_eq!(reader.read_bits(64), Some(u64::MAX));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_set_bits() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(0b111, 3);
+        writer.write_bits(0, 11);
+        writer.write_bits(0b11, 2);
+        writer.set_bits(3, 0b101_0110_0101, 11);
+        assert_eq!(writer.reader().read_bits(16), Some(0b1111_0101_1001_0111));
+
+        writer.set_bits(0, 0, 16);
+        assert_eq!(writer.clone().into_bytes(), (vec![0, 0], 16));
+        writer.write_bits(u64::MAX, 64);
+        writer.set_bits(13, u64::MAX >> 1, 64);
+        let mut reader = writer.reader();
+        assert_eq!(reader.read_bits(13), Some(0));
+        assert_eq!(reader.read_bits(64), Some(u64::MAX >> 1));
+        assert_eq!(reader.read_bits(3), Some(0b111));
+    }
+
+    #[test]
+    fn test_append() {
+        let mut first = BitWriter::new();
+        first.write_bits(0b101, 3);
+        let mut second = BitWriter::new();
+        second.write_bits(0b1_1001_1100, 9);
+        first.append(&second);
+        assert_eq!(first.into_bytes(), (vec![0b1011_1001, 0b1100_0000], 12));
+    }
+}
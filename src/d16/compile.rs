@@ -0,0 +1,675 @@
+use super::bigint::BigUint;
+use super::encode::to_hex;
+use super::{LengthType, Operator, Packet, DAY};
+use crate::error::{parse_number, Error, Result};
+
+/// How packets without a version in the source get one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Versions {
+    /// The same version for every packet.
+    Fixed(u8),
+    /// The position of the packet in the tree, in pre-order, modulo 8.
+    Counting,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    SExpr,
+    Infix,
+}
+
+impl Syntax {
+    /// Source that opens a parenthesis on an operator name, or on any other word, is an
+    /// S-expression, anything else is infix. Infix that starts with a call in parentheses,
+    /// `(min(1, 2) + 3)`, needs [`compile_as`].
+    pub fn detect(source: &str) -> Syntax {
+        match tokenize(source).as_deref() {
+            Ok(["(", head, ..])
+                if operator(head).is_some()
+                    || head.starts_with(|c: char| c.is_ascii_alphabetic()) =>
+            {
+                Syntax::SExpr
+            }
+            _ => Syntax::Infix,
+        }
+    }
+}
+
+/// Compiles `source` into a packet tree. Two notations are accepted:
+///
+/// - S-expressions, `(sum 1 (product 2 3) (gt 4 5))`, with the operators `sum`, `product`,
+///   `min`, `max`, `gt`, `lt` and `eq` (or `+`, `*`, `>`, `<` and `==`);
+/// - infix, `1 + 2*3 + (4 > 5)`, where comparisons bind loosest, then `+`, then `*`, and
+///   `min(..)` and `max(..)` are written as calls.
+///
+/// A packet's version may be given by following its literal, operator name, parenthesised
+/// group or call with `@v`, as in `(sum@3 1@5 2)` or `(1 + 2)@3 * 4`; the others get theirs
+/// from a [`Versions`] policy.
+pub fn compile(source: &str, versions: Versions) -> Result<Packet> {
+    compile_as(source, Syntax::detect(source), versions)
+}
+
+pub fn compile_as(source: &str, syntax: Syntax, versions: Versions) -> Result<Packet> {
+    let mut parser = Parser {
+        source,
+        tokens: tokenize(source)?,
+        next: 0,
+    };
+    let expr = match syntax {
+        Syntax::SExpr => parser.sexpr()?,
+        Syntax::Infix => parser.infix()?,
+    };
+    if let Some(token) = parser.peek() {
+        return Err(parser.unexpected(token));
+    }
+    build(source, expr, versions)
+}
+
+/// Compiles `source` into a hex transmission.
+pub fn compile_to_hex(source: &str, versions: Versions, length_type: LengthType) -> Result<String> {
+    to_hex(&compile(source, versions)?, length_type)
+}
+
+/// Splits `source` into numbers, words and symbols, each a slice of `source`.
+fn tokenize(source: &str) -> Result<Vec<&str>> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_ascii_digit() {
+            rest.find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len())
+        } else if c.is_ascii_alphabetic() {
+            rest.find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len())
+        } else if rest.starts_with("==") {
+            2
+        } else if "()+*<>,@".contains(c) {
+            1
+        } else {
+            return Err(Error::at(
+                DAY,
+                source,
+                rest,
+                format!("unexpected character `{}`", c),
+            ));
+        };
+        tokens.push(&rest[..len]);
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+fn operator(name: &str) -> Option<Operator> {
+    Some(match name {
+        "sum" | "+" => Operator::Sum,
+        "product" | "*" => Operator::Product,
+        "min" => Operator::Min,
+        "max" => Operator::Max,
+        "gt" | ">" => Operator::Gt,
+        "lt" | "<" => Operator::Lt,
+        "eq" | "==" => Operator::Eq,
+        _ => return None,
+    })
+}
+
+/// A parsed expression, before versions are settled.
+struct Expr<'a> {
+    /// The literal or operator token, where errors about the expression point.
+    token: &'a str,
+    version: Option<u8>,
+    kind: Kind<'a>,
+}
+
+enum Kind<'a> {
+    Literal(BigUint),
+    Operator(Operator, Vec<Expr<'a>>),
+}
+
+impl<'a> Expr<'a> {
+    fn operator(token: &'a str, version: Option<u8>, operator: Operator) -> Self {
+        Expr {
+            token,
+            version,
+            kind: Kind::Operator(operator, Vec::new()),
+        }
+    }
+
+    /// Adds an operand to an operator expression.
+    fn push(&mut self, operand: Expr<'a>) {
+        if let Kind::Operator(_, operands) = &mut self.kind {
+            operands.push(operand);
+        }
+    }
+
+    /// The expression for `operands` joined by `symbol`: the operand itself if there is
+    /// only one, else one packet over all of them, pointed at by the first `symbol`.
+    fn chain(symbol: Option<&'a str>, mut operands: Vec<Expr<'a>>) -> Self {
+        match symbol {
+            Some(token) if operands.len() > 1 => Expr {
+                token,
+                version: None,
+                kind: Kind::Operator(operator(token).unwrap(), operands),
+            },
+            _ => operands.pop().unwrap(),
+        }
+    }
+}
+
+/// Drops the operands from a list rather than recursively.
+impl Drop for Expr<'_> {
+    fn drop(&mut self) {
+        if let Kind::Operator(_, operands) = &mut self.kind {
+            let mut pending = std::mem::take(operands);
+            while let Some(mut expr) = pending.pop() {
+                if let Kind::Operator(_, operands) = &mut expr.kind {
+                    pending.append(operands);
+                }
+            }
+        }
+    }
+}
+
+/// An infix comparison being parsed, with the operands read so far at each precedence.
+struct Frame<'a> {
+    opener: Opener<'a>,
+    /// The left side of the comparison and its operator, once it is found.
+    left: Option<(Expr<'a>, &'a str)>,
+    /// The terms of the sum, and its first `+`.
+    terms: Vec<Expr<'a>>,
+    plus: Option<&'a str>,
+    /// The factors of the current term, and its first `*`.
+    factors: Vec<Expr<'a>>,
+    star: Option<&'a str>,
+}
+
+/// What a comparison is parsed for.
+enum Opener<'a> {
+    /// The whole source.
+    Root,
+    /// A group in parentheses.
+    Group,
+    /// An argument of `min(..)` or `max(..)`, which gets the arguments before it.
+    Call(Expr<'a>),
+}
+
+impl<'a> Frame<'a> {
+    fn new(opener: Opener<'a>) -> Self {
+        Frame {
+            opener,
+            left: None,
+            terms: Vec::new(),
+            plus: None,
+            factors: Vec::new(),
+            star: None,
+        }
+    }
+
+    /// Moves the product of the factors so far to the terms.
+    fn end_term(&mut self) {
+        let term = Expr::chain(self.star.take(), std::mem::take(&mut self.factors));
+        self.terms.push(term);
+    }
+
+    /// The sum of the terms so far, the current one included.
+    fn additive(&mut self) -> Expr<'a> {
+        self.end_term();
+        Expr::chain(self.plus.take(), std::mem::take(&mut self.terms))
+    }
+
+    /// The comparison, once its last operand is read.
+    fn comparison(&mut self) -> Expr<'a> {
+        let right = self.additive();
+        match self.left.take() {
+            Some((left, token)) => {
+                let mut expr = Expr::operator(token, None, operator(token).unwrap());
+                expr.push(left);
+                expr.push(right);
+                expr
+            }
+            None => right,
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<&'a str>,
+    next: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.next).copied()
+    }
+
+    fn advance(&mut self) -> Result<&'a str> {
+        let token = self.peek().ok_or_else(|| {
+            let end = &self.source[self.source.len()..];
+            Error::at(DAY, self.source, end, "the expression ends too early")
+        })?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn unexpected(&self, token: &str) -> Error {
+        Error::at(DAY, self.source, token, format!("unexpected `{}`", token))
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<&'a str> {
+        let token = self.advance()?;
+        if token != expected {
+            return Err(Error::at(
+                DAY,
+                self.source,
+                token,
+                format!("expected `{}`, found `{}`", expected, token),
+            ));
+        }
+        Ok(token)
+    }
+
+    /// An optional `@v` after an expression.
+    fn version(&mut self) -> Result<Option<u8>> {
+        if self.peek() != Some("@") {
+            return Ok(None);
+        }
+        self.advance()?;
+        let token = self.advance()?;
+        match parse_number::<u64>(DAY, self.source, token)? {
+            version @ 0..=7 => Ok(Some(version as u8)),
+            version => Err(Error::at(
+                DAY,
+                self.source,
+                token,
+                format!("version {} does not fit in 3 bits", version),
+            )),
+        }
+    }
+
+    /// A literal of any size.
+    fn literal(&mut self, token: &'a str) -> Result<Expr<'a>> {
+        let value = BigUint::from_decimal(token).ok_or_else(|| {
+            Error::at(
+                DAY,
+                self.source,
+                token,
+                format!("expected a number, found `{}`", token),
+            )
+        })?;
+        Ok(Expr {
+            token,
+            kind: Kind::Literal(value),
+            version: self.version()?,
+        })
+    }
+
+    /// An S-expression, read with a stack of the operators whose operands are being read
+    /// rather than recursively.
+    fn sexpr(&mut self) -> Result<Expr<'a>> {
+        let mut open: Vec<Expr<'a>> = Vec::new();
+        loop {
+            let token = self.advance()?;
+            if token == "(" {
+                let name = self.advance()?;
+                let operator = operator(name).ok_or_else(|| {
+                    Error::at(
+                        DAY,
+                        self.source,
+                        name,
+                        format!("unknown operator `{}`", name),
+                    )
+                })?;
+                open.push(Expr::operator(name, self.version()?, operator));
+            } else {
+                let literal = self.literal(token)?;
+                match open.last_mut() {
+                    Some(parent) => parent.push(literal),
+                    None => return Ok(literal),
+                }
+            }
+
+            while !open.is_empty() && self.peek() == Some(")") {
+                self.advance()?;
+                let expr = open.pop().unwrap();
+                match open.last_mut() {
+                    Some(parent) => parent.push(expr),
+                    None => return Ok(expr),
+                }
+            }
+        }
+    }
+
+    /// Infix, where comparisons bind loosest, then `+`, then `*`. Groups and calls are read
+    /// with a stack of the comparisons they are inside rather than recursively.
+    fn infix(&mut self) -> Result<Expr<'a>> {
+        let mut frames = vec![Frame::new(Opener::Root)];
+        loop {
+            // read operands and open groups and calls until an operand is complete
+            let token = self.advance()?;
+            let mut operand = match token {
+                "(" => {
+                    frames.push(Frame::new(Opener::Group));
+                    continue;
+                }
+                "min" | "max" => {
+                    self.expect("(")?;
+                    let call = Expr::operator(token, None, operator(token).unwrap());
+                    frames.push(Frame::new(Opener::Call(call)));
+                    continue;
+                }
+                _ if token.starts_with(|c: char| c.is_ascii_digit()) => self.literal(token)?,
+                _ => return Err(self.unexpected(token)),
+            };
+
+            // add it to the innermost comparison, closing the groups and calls it ends
+            loop {
+                let frame = frames.last_mut().unwrap();
+                frame.factors.push(operand);
+                match self.peek() {
+                    Some("*") => {
+                        let star = self.advance()?;
+                        frame.star.get_or_insert(star);
+                        break;
+                    }
+                    Some("+") => {
+                        let plus = self.advance()?;
+                        frame.end_term();
+                        frame.plus.get_or_insert(plus);
+                        break;
+                    }
+                    Some(token @ ("<" | ">" | "==")) => {
+                        if frame.left.is_some() {
+                            return Err(Error::at(
+                                DAY,
+                                self.source,
+                                token,
+                                "comparisons do not chain, add parentheses",
+                            ));
+                        }
+                        self.advance()?;
+                        let left = frame.additive();
+                        frame.left = Some((left, token));
+                        break;
+                    }
+                    _ => {}
+                }
+
+                let expr = frame.comparison();
+                match &mut frame.opener {
+                    Opener::Root => return Ok(expr),
+                    Opener::Call(call) if self.peek() == Some(",") => {
+                        self.advance()?;
+                        call.push(expr);
+                        break;
+                    }
+                    _ => {}
+                }
+                self.expect(")")?;
+                operand = match frames.pop().unwrap().opener {
+                    Opener::Call(mut call) => {
+                        call.push(expr);
+                        call
+                    }
+                    _ => expr,
+                };
+                if let Some(version) = self.version()? {
+                    operand.version = Some(version);
+                }
+            }
+        }
+    }
+}
+
+/// What is left to turn into packets.
+enum Step<'a> {
+    Expr(Expr<'a>),
+    /// Builds an operator packet from the last `count` packets built.
+    Operator {
+        version: u8,
+        operator: Operator,
+        count: usize,
+    },
+}
+
+/// Turns `expr` into packets, numbering them in pre-order.
+fn build(source: &str, expr: Expr, versions: Versions) -> Result<Packet> {
+    let mut position = 0;
+    let mut stack = vec![Step::Expr(expr)];
+    let mut built: Vec<Packet> = Vec::new();
+
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Expr(mut expr) => {
+                let version = expr.version.unwrap_or(match versions {
+                    Versions::Fixed(version) => version,
+                    Versions::Counting => (position % 8) as u8,
+                });
+                position += 1;
+
+                match &mut expr.kind {
+                    Kind::Literal(value) => built.push(Packet::big_literal(version, value.clone())),
+                    Kind::Operator(operator, operands) => {
+                        if !operator.accepts(operands.len()) {
+                            return Err(Error::at(
+                                DAY,
+                                source,
+                                expr.token,
+                                format!(
+                                    "{:?} cannot apply to {} subpackets",
+                                    operator,
+                                    operands.len()
+                                ),
+                            ));
+                        }
+                        stack.push(Step::Operator {
+                            version,
+                            operator: *operator,
+                            count: operands.len(),
+                        });
+                        stack.extend(std::mem::take(operands).into_iter().rev().map(Step::Expr));
+                    }
+                }
+            }
+            Step::Operator {
+                version,
+                operator,
+                count,
+            } => {
+                let subpackets = built.split_off(built.len() - count);
+                built.push(Packet::operator(version, operator, subpackets));
+            }
+        }
+    }
+    Ok(built.pop().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_transmission;
+    use super::*;
+
+    fn lit(value: u64) -> Packet {
+        Packet::literal(0, value)
+    }
+
+    fn op(operator: Operator, subpackets: Vec<Packet>) -> Packet {
+        Packet::operator(0, operator, subpackets)
+    }
+
+    #[test]
+    fn test_sexpr() {
+        let expected = op(
+            Operator::Sum,
+            vec![
+                lit(1),
+                op(Operator::Product, vec![lit(2), lit(3)]),
+                op(Operator::Gt, vec![lit(4), lit(5)]),
+            ],
+        );
+        let source = "(sum 1 (product 2 3) (gt 4 5))";
+        assert_eq!(Syntax::detect(source), Syntax::SExpr);
+        assert_eq!(compile(source, Versions::Fixed(0)), Ok(expected.clone()));
+        assert_eq!(
+            compile("(+ 1 (* 2 3) (> 4 5))", Versions::Fixed(0)),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn test_infix() {
+        let source = "1 + 2*3 + (4 > 5)";
+        assert_eq!(Syntax::detect(source), Syntax::Infix);
+        assert_eq!(
+            compile(source, Versions::Fixed(0)),
+            compile("(sum 1 (product 2 3) (gt 4 5))", Versions::Fixed(0))
+        );
+        assert_eq!(
+            compile("max(1, 2 + 3) * (1 + 2 == 3)", Versions::Fixed(0)),
+            Ok(op(
+                Operator::Product,
+                vec![
+                    op(
+                        Operator::Max,
+                        vec![lit(1), op(Operator::Sum, vec![lit(2), lit(3)])]
+                    ),
+                    op(
+                        Operator::Eq,
+                        vec![op(Operator::Sum, vec![lit(1), lit(2)]), lit(3)]
+                    ),
+                ]
+            ))
+        );
+        // parentheses keep their own packet instead of joining the chain
+        assert_eq!(
+            compile("1 + (2 + 3)", Versions::Fixed(0)),
+            Ok(op(
+                Operator::Sum,
+                vec![lit(1), op(Operator::Sum, vec![lit(2), lit(3)])]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_versions() {
+        let packet = compile("(sum@7 1 (min 2@6 3))", Versions::Counting).unwrap();
+        assert_eq!(
+            packet,
+            Packet::operator(
+                7,
+                Operator::Sum,
+                vec![
+                    Packet::literal(1, 1),
+                    Packet::operator(
+                        2,
+                        Operator::Min,
+                        vec![Packet::literal(6, 2), Packet::literal(4, 3)]
+                    )
+                ]
+            )
+        );
+        assert_eq!(
+            compile("(1 + 2)@3 * 4", Versions::Fixed(5)).unwrap().kind,
+            compile("(* (+@3 1 2) 4)", Versions::Fixed(5)).unwrap().kind
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let deep = (0..40).fold("1".to_string(), |inner, n| format!("(max {} {})", n, inner));
+        let huge = format!("{} * 1 == {}", u64::MAX, u64::MAX);
+        let wide = "(product 99999999999999999999999 2)";
+        for source in &["1 + 2*3 + (4 > 5)", &deep, &huge, wide] {
+            let packet = compile(source, Versions::Counting).unwrap();
+            for &length_type in &[LengthType::Bits, LengthType::Count] {
+                let hex = compile_to_hex(source, Versions::Counting, length_type).unwrap();
+                assert_eq!(parse_transmission(&hex), Ok(packet.clone()));
+            }
+        }
+        let hex = compile_to_hex(&huge, Versions::Counting, LengthType::Bits).unwrap();
+        assert_eq!(super::super::p2(&hex), Ok(1));
+
+        let hex = compile_to_hex(wide, Versions::Counting, LengthType::Bits).unwrap();
+        assert_eq!(
+            super::super::p2_big(&hex).unwrap().to_string(),
+            "199999999999999999999998"
+        );
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let depth = 100_000;
+        let sexpr = format!("{}1{}", "(sum ".repeat(depth), ")".repeat(depth));
+        let infix = format!("{}1{}", "1 + (".repeat(depth), ")".repeat(depth));
+        let calls = format!("{}1{}", "max(".repeat(depth), ")".repeat(depth));
+        // one packet per level, and the innermost literal; infix adds a literal per level
+        let n = depth as u64;
+        for (source, packets, value) in [
+            (sexpr, n + 1, 1),
+            (infix, 2 * n + 1, n + 1),
+            (calls, n + 1, 1),
+        ]
+        .iter()
+        {
+            let packet = compile(source, Versions::Fixed(1)).unwrap();
+            assert_eq!(packet.version_sum(), *packets, "{}", &source[..9]);
+            assert_eq!(packet.checked_value(), Ok(*value));
+        }
+        // a deep expression is dropped without recursion when it fails to compile
+        let err = compile(&format!("{}1", "(sum ".repeat(depth)), Versions::Fixed(1));
+        assert_eq!(err.unwrap_err().reason, "the expression ends too early");
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = compile("(sum 1 (gt 2 3 4))", Versions::Counting).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 16, line 1, column 9: Gt cannot apply to 3 subpackets"
+        );
+
+        let err = compile("1 < 2 < 3", Versions::Counting).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 16, line 1, column 7: comparisons do not chain, add parentheses"
+        );
+
+        let err = compile("(sum 1 2", Versions::Counting).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 16, line 1, column 9: the expression ends too early"
+        );
+
+        let err = compile("1 + 2@8", Versions::Counting).unwrap_err();
+        assert_eq!(err.reason, "version 8 does not fit in 3 bits");
+
+        let err = compile("(avg 1 2)", Versions::Counting).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 16, line 1, column 2: unknown operator `avg`"
+        );
+
+        let err = compile("max(1, 2", Versions::Counting).unwrap_err();
+        assert_eq!(err.reason, "the expression ends too early");
+
+        let err = compile("(1 + 2, 3)", Versions::Counting).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 16, line 1, column 7: expected `)`, found `,`"
+        );
+
+        let err = compile("(sum 1 2))", Versions::Counting).unwrap_err();
+        assert_eq!(err.reason, "unexpected `)`");
+
+        let err = compile("2 - 1", Versions::Counting).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 16, line 1, column 3: unexpected character `-`"
+        );
+
+        let err = compile("(sum 1 x)", Versions::Counting).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 16, line 1, column 8: expected a number, found `x`"
+        );
+    }
+}
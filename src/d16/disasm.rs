@@ -0,0 +1,325 @@
+use std::convert::Infallible;
+use std::fmt::{self, Display, Write};
+
+use serde::Serialize;
+
+use super::bigint::BigUint;
+use super::{parse_with_layout, Layout, PacketType};
+use crate::error::Result;
+
+/// A decoded packet with where it sits in the transmission and what it evaluates to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// Bit offset of the packet header from the start of the transmission.
+    pub offset: usize,
+    /// Length in bits of the packet, subpackets included.
+    pub length: usize,
+    pub version: u8,
+    pub type_id: u8,
+    /// `literal`, or the name of the operator.
+    pub kind: &'static str,
+    /// 0 or 1 for operator packets.
+    pub length_type: Option<u8>,
+    /// The exact value of the subtree rooted at this packet.
+    pub value: BigUint,
+    pub subpackets: Vec<Node>,
+}
+
+/// Decodes `input` into a tree of [`Node`]s.
+pub fn disassemble(input: &str) -> Result<Node> {
+    let (packet, layouts) = parse_with_layout(input)?;
+    let node = packet.fold(|packet, position, subpackets: Vec<Node>| {
+        let layout: Layout = layouts[position];
+        let (kind, value) = match &packet.kind {
+            PacketType::Literal(value) => ("literal", BigUint::from(*value)),
+            PacketType::WideLiteral(value) => ("literal", value.clone()),
+            PacketType::Operator(operator, _) => {
+                let values: Vec<BigUint> = subpackets.iter().map(|n| n.value.clone()).collect();
+                (operator.name(), operator.apply(&values))
+            }
+        };
+        Ok::<_, Infallible>(Node {
+            offset: layout.offset,
+            length: layout.length,
+            version: packet.version,
+            type_id: packet.type_id(),
+            kind,
+            length_type: layout.length_type.map(|t| t.id()),
+            value,
+            subpackets,
+        })
+    });
+    Ok(node.unwrap_or_else(|never| match never {}))
+}
+
+/// What is left to print of a tree of nodes.
+enum Step<'a> {
+    /// A node, at a nesting depth, and whether it is the last of its siblings.
+    Node(&'a Node, usize, bool),
+    /// The end of the subpackets of a node.
+    Close(usize, bool),
+}
+
+impl Node {
+    /// The tree as pretty-printed JSON, the fields of each node in declaration order.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json).unwrap();
+        json
+    }
+
+    /// Writes the tree as [`to_json`](Node::to_json) gives it.
+    pub fn write_json(&self, json: &mut impl Write) -> fmt::Result {
+        let mut stack = vec![Step::Node(self, 0, true)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Node(node, depth, last) => {
+                    let indent = depth * 2;
+                    json.write_str("{\n")?;
+                    for (name, value) in node.json_fields() {
+                        writeln!(
+                            json,
+                            "{:indent$}  \"{}\": {},",
+                            "",
+                            name,
+                            value,
+                            indent = indent
+                        )?;
+                    }
+                    if node.subpackets.is_empty() {
+                        writeln!(json, "{:indent$}  \"subpackets\": []", "", indent = indent)?;
+                        close(json, indent, last, stack.is_empty())?;
+                    } else {
+                        writeln!(json, "{:indent$}  \"subpackets\": [", "", indent = indent)?;
+                        stack.push(Step::Close(depth, last));
+                        let count = node.subpackets.len();
+                        for (i, subpacket) in node.subpackets.iter().enumerate().rev() {
+                            stack.push(Step::Node(subpacket, depth + 2, i + 1 == count));
+                        }
+                    }
+                }
+                Step::Close(depth, last) => {
+                    writeln!(json, "{:indent$}  ]", "", indent = depth * 2)?;
+                    close(json, depth * 2, last, stack.is_empty())?;
+                }
+            }
+            // a node in a list starts on its own line
+            if let Some(Step::Node(_, depth, _)) = stack.last() {
+                write!(json, "{:indent$}", "", indent = depth * 2)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every field but the subpackets, as JSON.
+    fn json_fields(&self) -> [(&'static str, String); 7] {
+        fn json(value: impl Serialize) -> String {
+            serde_json::to_string(&value).unwrap()
+        }
+        [
+            ("offset", json(self.offset)),
+            ("length", json(self.length)),
+            ("version", json(self.version)),
+            ("type_id", json(self.type_id)),
+            ("kind", json(self.kind)),
+            ("length_type", json(self.length_type)),
+            ("value", json(&self.value)),
+        ]
+    }
+}
+
+/// Ends a JSON object with a comma unless it is the `last` in its list, and the line it is
+/// on unless it is the `outermost`.
+fn close(json: &mut impl Write, indent: usize, last: bool, outermost: bool) -> fmt::Result {
+    write!(json, "{:indent$}}}", "", indent = indent)?;
+    match (last, outermost) {
+        (_, true) => Ok(()),
+        (true, false) => json.write_str("\n"),
+        (false, false) => json.write_str(",\n"),
+    }
+}
+
+/// One line per packet, subpackets indented under their operator.
+impl Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pending = vec![(self, 0)];
+        while let Some((node, depth)) = pending.pop() {
+            write!(
+                f,
+                "{:indent$}bits {}..{} v{} type {} {}",
+                "",
+                node.offset,
+                node.offset + node.length,
+                node.version,
+                node.type_id,
+                node.kind,
+                indent = depth * 2
+            )?;
+            if let Some(length_type) = node.length_type {
+                write!(
+                    f,
+                    ", length type {}, {} subpackets",
+                    length_type,
+                    node.subpackets.len()
+                )?;
+            }
+            writeln!(f, " = {}", node.value)?;
+            pending.extend(node.subpackets.iter().rev().map(|n| (n, depth + 1)));
+        }
+        Ok(())
+    }
+}
+
+/// Drops the subpackets from a list rather than recursively.
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut pending = std::mem::take(&mut self.subpackets);
+        while let Some(mut node) = pending.pop() {
+            pending.append(&mut node.subpackets);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::encode::to_hex;
+    use super::super::{LengthType, Operator, Packet};
+    use super::*;
+
+    #[test]
+    fn test_disassemble_text() {
+        assert_eq!(
+            disassemble("38006F45291200").unwrap().to_string(),
+            "bits 0..49 v1 type 6 lt, length type 0, 2 subpackets = 1
+  bits 22..33 v6 type 4 literal = 10
+  bits 33..49 v2 type 4 literal = 20
+"
+        );
+        assert_eq!(
+            disassemble("9C0141080250320F1802104A08")
+                .unwrap()
+                .to_string(),
+            "bits 0..102 v4 type 7 eq, length type 0, 2 subpackets = 1
+  bits 22..62 v2 type 0 sum, length type 1, 2 subpackets = 4
+    bits 40..51 v2 type 4 literal = 1
+    bits 51..62 v4 type 4 literal = 3
+  bits 62..102 v6 type 1 product, length type 1, 2 subpackets = 4
+    bits 80..91 v0 type 4 literal = 2
+    bits 91..102 v2 type 4 literal = 2
+"
+        );
+    }
+
+    #[test]
+    fn test_nested_json() {
+        let tree = disassemble("9C0141080250320F1802104A08").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&tree.to_json()).unwrap();
+        assert_eq!(json["value"], 1);
+        assert_eq!(json["subpackets"][0]["kind"], "sum");
+        assert_eq!(json["subpackets"][1]["subpackets"][1]["offset"], 91);
+        assert_eq!(
+            disassemble("38006F45291200").unwrap().to_json(),
+            r#"{
+  "offset": 0,
+  "length": 49,
+  "version": 1,
+  "type_id": 6,
+  "kind": "lt",
+  "length_type": 0,
+  "value": 1,
+  "subpackets": [
+    {
+      "offset": 22,
+      "length": 11,
+      "version": 6,
+      "type_id": 4,
+      "kind": "literal",
+      "length_type": null,
+      "value": 10,
+      "subpackets": []
+    },
+    {
+      "offset": 33,
+      "length": 16,
+      "version": 2,
+      "type_id": 4,
+      "kind": "literal",
+      "length_type": null,
+      "value": 20,
+      "subpackets": []
+    }
+  ]
+}"#
+        );
+    }
+
+    /// Counts the lines written instead of keeping them, as deep trees are mostly indentation.
+    #[derive(Default)]
+    struct Lines {
+        count: usize,
+        last: String,
+        current: String,
+    }
+
+    impl Write for Lines {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for c in s.chars() {
+                if c == '\n' {
+                    self.count += 1;
+                    self.last = std::mem::take(&mut self.current);
+                } else if c != ' ' || !self.current.is_empty() {
+                    self.current.push(c);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let mut packet = Packet::literal(1, 7);
+        for _ in 0..20_000 {
+            packet = Packet::operator(1, Operator::Sum, vec![packet]);
+        }
+        let hex = to_hex(&packet, LengthType::Count).unwrap();
+        let tree = disassemble(&hex).unwrap();
+        assert_eq!(tree.value, BigUint::from(7));
+
+        // print the innermost 3000 levels only: the whole tree is gigabytes of indentation
+        let mut node = &tree;
+        for _ in 0..17_000 {
+            node = &node.subpackets[0];
+        }
+
+        let mut text = Lines::default();
+        write!(text, "{}", node).unwrap();
+        assert_eq!(text.count, 3_001);
+        assert_eq!(text.last, "bits 360000..360011 v1 type 4 literal = 7");
+
+        let mut json = Lines::default();
+        node.write_json(&mut json).unwrap();
+        // eleven lines an operator and ten for the literal, its last without a newline
+        assert_eq!(json.count, 3_000 * 11 + 10 - 1);
+        assert_eq!(json.last, "]");
+        assert_eq!(json.current, "}");
+    }
+
+    #[test]
+    fn test_disassemble_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&disassemble("D2FE28").unwrap().to_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "offset": 0,
+                "length": 21,
+                "version": 6,
+                "type_id": 4,
+                "kind": "literal",
+                "length_type": null,
+                "value": 2021,
+                "subpackets": [],
+            })
+        );
+    }
+}
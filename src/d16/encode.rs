@@ -0,0 +1,289 @@
+use super::bigint::BigUint;
+use super::bits::BitWriter;
+use super::{Layout, LengthType, Operator, Packet, PacketType, DAY};
+use crate::error::{Error, Result};
+
+/// Writes `packet` as a BITS bit string, giving every operator packet the length type
+/// `length_type`.
+pub fn encode(packet: &Packet, length_type: LengthType) -> Result<BitWriter> {
+    write_packets(packet, |_| Ok(length_type))
+}
+
+/// Writes `packet` as a BITS bit string, giving each operator packet the length type of
+/// its layout, as [`decode_with_layout`](super::decode_with_layout) gives them in
+/// pre-order, so a decoded transmission is written back bit for bit.
+pub fn encode_with_layout(packet: &Packet, layouts: &[Layout]) -> Result<BitWriter> {
+    write_packets(packet, |index| {
+        layouts
+            .get(index)
+            .and_then(|layout| layout.length_type)
+            .ok_or_else(|| {
+                Error::new(
+                    DAY,
+                    format!("packet {} has no layout with a length type", index),
+                )
+            })
+    })
+}
+
+/// Writes `packet` as a hex transmission, padded with zeros to a whole number of bytes
+/// like the puzzle's own transmissions.
+pub fn to_hex(packet: &Packet, length_type: LengthType) -> Result<String> {
+    Ok(hex(encode(packet, length_type)?))
+}
+
+/// Writes `packet` as a hex transmission with the length types of `layouts`.
+pub fn to_hex_with_layout(packet: &Packet, layouts: &[Layout]) -> Result<String> {
+    Ok(hex(encode_with_layout(packet, layouts)?))
+}
+
+fn hex(writer: BitWriter) -> String {
+    let (bytes, _) = writer.into_bytes();
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// What is left to write of a packet tree.
+enum Step<'a> {
+    Packet(&'a Packet),
+    /// The length field at bit `field` of an operator whose subpackets start at `start`,
+    /// filled in once they are written.
+    Length {
+        field: usize,
+        start: usize,
+        length_type: LengthType,
+        operator: Operator,
+        count: usize,
+    },
+}
+
+/// Writes the packets of the tree in pre-order. `length_type` gives the length type of
+/// the operator packet at a pre-order index.
+fn write_packets(
+    packet: &Packet,
+    mut length_type: impl FnMut(usize) -> Result<LengthType>,
+) -> Result<BitWriter> {
+    let mut writer = BitWriter::new();
+    let mut index = 0;
+    let mut stack = vec![Step::Packet(packet)];
+
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Packet(packet) => {
+                if packet.version > 7 {
+                    return Err(Error::new(
+                        DAY,
+                        format!("version {} does not fit in 3 bits", packet.version),
+                    ));
+                }
+                writer.write_bits(packet.version as u64, 3);
+                writer.write_bits(packet.type_id() as u64, 3);
+                index += 1;
+
+                match &packet.kind {
+                    PacketType::Literal(value) => {
+                        write_literal(&mut writer, &BigUint::from(*value))
+                    }
+                    PacketType::WideLiteral(value) => write_literal(&mut writer, value),
+                    PacketType::Operator(operator, subpackets) => {
+                        if !operator.accepts(subpackets.len()) {
+                            return Err(Error::new(
+                                DAY,
+                                format!(
+                                    "{:?} cannot apply to {} subpackets",
+                                    operator,
+                                    subpackets.len()
+                                ),
+                            ));
+                        }
+                        let length_type = length_type(index - 1)?;
+                        writer.write_bits(length_type.id() as u64, 1);
+                        let field = writer.len();
+                        writer.write_bits(0, length_type.field_width());
+                        stack.push(Step::Length {
+                            field,
+                            start: writer.len(),
+                            length_type,
+                            operator: *operator,
+                            count: subpackets.len(),
+                        });
+                        stack.extend(subpackets.iter().rev().map(Step::Packet));
+                    }
+                }
+            }
+            Step::Length {
+                field,
+                start,
+                length_type,
+                operator,
+                count,
+            } => {
+                let length = match length_type {
+                    LengthType::Bits => writer.len() - start,
+                    LengthType::Count => count,
+                };
+                let width = length_type.field_width();
+                if length >> width != 0 {
+                    return Err(Error::new(
+                        DAY,
+                        format!(
+                            "the {} subpackets of {:?} do not fit length type {}",
+                            count,
+                            operator,
+                            length_type.id()
+                        ),
+                    ));
+                }
+                writer.set_bits(field, length as u64, width);
+            }
+        }
+    }
+    Ok(writer)
+}
+
+/// Writes `value` in groups of 4 bits, each after a bit saying whether another follows.
+fn write_literal(writer: &mut BitWriter, value: &BigUint) {
+    let nibbles = value.nibbles();
+    for (i, &nibble) in nibbles.iter().enumerate() {
+        writer.write_bit(i + 1 < nibbles.len());
+        writer.write_bits(nibble as u64, 4);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{parse_transmission, parse_with_layout};
+    use super::*;
+
+    const OPERATORS: [Operator; 7] = [
+        Operator::Sum,
+        Operator::Product,
+        Operator::Min,
+        Operator::Max,
+        Operator::Gt,
+        Operator::Lt,
+        Operator::Eq,
+    ];
+
+    /// Random packet trees from a fixed linear congruential generator, so every run
+    /// checks the same trees.
+    struct Trees(u64);
+
+    impl Trees {
+        fn next(&mut self) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            self.0 >> 11
+        }
+
+        fn packet(&mut self, depth: usize) -> Packet {
+            let version = (self.next() % 8) as u8;
+            if depth == 0 || self.next().is_multiple_of(3) {
+                // spread literals over every group count
+                let value = self.next() >> (self.next() % 53);
+                return Packet::literal(version, value);
+            }
+            let operator = OPERATORS[(self.next() % 7) as usize];
+            let count = match operator {
+                Operator::Gt | Operator::Lt | Operator::Eq => 2,
+                _ => 1 + (self.next() % 4) as usize,
+            };
+            let subpackets = (0..count).map(|_| self.packet(depth - 1)).collect();
+            Packet::operator(version, operator, subpackets)
+        }
+    }
+
+    #[test]
+    fn test_encode_examples() {
+        let literal = Packet::literal(6, 2021);
+        assert_eq!(to_hex(&literal, LengthType::Bits), Ok("D2FE28".to_string()));
+
+        let lt = parse_transmission("38006F45291200").unwrap();
+        assert_eq!(
+            to_hex(&lt, LengthType::Bits),
+            Ok("38006F45291200".to_string())
+        );
+
+        let max = parse_transmission("EE00D40C823060").unwrap();
+        assert_eq!(
+            to_hex(&max, LengthType::Count),
+            Ok("EE00D40C823060".to_string())
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut trees = Trees(16);
+        for _ in 0..200 {
+            let packet = trees.packet(5);
+            for &length_type in &[LengthType::Bits, LengthType::Count] {
+                let hex = to_hex(&packet, length_type).unwrap();
+                assert_eq!(parse_transmission(&hex), Ok(packet.clone()), "{}", hex);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_with_layout() {
+        let input = include_str!("../inputs/d16.txt").trim();
+        let (packet, layouts) = parse_with_layout(input).unwrap();
+        // the puzzle mixes both length types
+        for &length_type in &[LengthType::Bits, LengthType::Count] {
+            assert!(layouts.iter().any(|l| l.length_type == Some(length_type)));
+        }
+        assert_eq!(to_hex_with_layout(&packet, &layouts), Ok(input.to_string()));
+
+        let err = to_hex_with_layout(&packet, &layouts[..3]).unwrap_err();
+        assert_eq!(err.reason, "packet 3 has no layout with a length type");
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let mut packet = Packet::literal(1, 7);
+        for _ in 0..20_000 {
+            packet = Packet::operator(1, Operator::Sum, vec![packet]);
+        }
+        let hex = to_hex(&packet, LengthType::Count).unwrap();
+        let (decoded, layouts) = parse_with_layout(&hex).unwrap();
+        assert_eq!(layouts.len(), 20_001);
+        assert_eq!(decoded.version_sum(), 20_001);
+        assert_eq!(decoded.checked_value(), Ok(7));
+
+        // the outermost packets are far more than 15 bits long
+        let err = to_hex(&packet, LengthType::Bits).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "the 1 subpackets of Sum do not fit length type 0"
+        );
+    }
+
+    #[test]
+    fn test_literal_groups() {
+        for &(value, bits) in &[(0, 11), (15, 11), (16, 16), (u64::MAX, 86)] {
+            let packet = Packet::literal(0, value);
+            assert_eq!(encode(&packet, LengthType::Bits).unwrap().len(), bits);
+            let hex = to_hex(&packet, LengthType::Bits).unwrap();
+            assert_eq!(parse_transmission(&hex), Ok(packet));
+        }
+    }
+
+    #[test]
+    fn test_bad_packets() {
+        let err = to_hex(&Packet::literal(8, 1), LengthType::Bits).unwrap_err();
+        assert_eq!(err.reason, "version 8 does not fit in 3 bits");
+
+        let eq = Packet::operator(0, Operator::Eq, vec![Packet::literal(0, 1)]);
+        let err = to_hex(&eq, LengthType::Bits).unwrap_err();
+        assert_eq!(err.reason, "Eq cannot apply to 1 subpackets");
+
+        let wide = Packet::operator(0, Operator::Sum, vec![Packet::literal(0, 1); 2048]);
+        let err = to_hex(&wide, LengthType::Count).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "the 2048 subpackets of Sum do not fit length type 1"
+        );
+        // 2048 literals of 11 bits take 22528 bits, which length type 0 can describe
+        assert!(to_hex(&wide, LengthType::Bits).is_ok());
+    }
+}
@@ -0,0 +1,183 @@
+use std::io::{BufRead, BufReader, Read};
+
+use super::{decode, DecodeError, Packet, DAY};
+use crate::error::{Error, Location, Result};
+
+/// A transmission from a stream, by the line it was read from.
+#[derive(Debug)]
+pub struct Transmission {
+    /// 1-based line of the transmission in the stream.
+    pub line: usize,
+    /// A read error ends the stream; a malformed transmission does not, the next ones are
+    /// still decoded.
+    pub packet: Result<Packet>,
+}
+
+/// Decodes one hex transmission per line as the lines are read, skipping blank lines.
+pub struct Transmissions<R> {
+    reader: R,
+    /// The current line, reused from one line to the next.
+    buffer: Vec<u8>,
+    line: usize,
+    failed: bool,
+}
+
+/// The transmissions of `reader`, buffered.
+pub fn transmissions<R: Read>(reader: R) -> Transmissions<BufReader<R>> {
+    Transmissions::new(BufReader::new(reader))
+}
+
+impl<R: BufRead> Transmissions<R> {
+    pub fn new(reader: R) -> Self {
+        Transmissions {
+            reader,
+            buffer: Vec::new(),
+            line: 0,
+            failed: false,
+        }
+    }
+}
+
+impl<R> Transmissions<R> {
+    /// The crate error for `err`, pointing at the hex digit of the current line that holds
+    /// the bit decoding stopped at.
+    fn locate(&self, line: &str, err: &DecodeError) -> Error {
+        let hex = line.trim();
+        let digit = match *err {
+            DecodeError::InvalidHex { index, .. } => hex[..index].chars().count(),
+            _ => (err.offset() / 4).min(hex.chars().count()),
+        };
+        let indent = line[..line.len() - line.trim_start().len()].chars().count();
+        Error {
+            location: Some(Location {
+                line: self.line,
+                column: indent + digit + 1,
+            }),
+            ..err.to_error(line)
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Transmissions<R> {
+    type Item = Transmission;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            self.buffer.clear();
+            let read = self.reader.read_until(b'\n', &mut self.buffer);
+            self.line += 1;
+            let packet = match read {
+                Ok(0) => return None,
+                Err(err) => {
+                    self.failed = true;
+                    Err(Error::new(
+                        DAY,
+                        format!("cannot read the transmissions: {}", err),
+                    ))
+                }
+                // bytes that are not UTF-8 cannot be hex digits, and are reported as such
+                Ok(_) => match String::from_utf8_lossy(&self.buffer) {
+                    line if line.trim().is_empty() => continue,
+                    line => decode(&line).map_err(|err| self.locate(&line, &err)),
+                },
+            };
+            return Some(Transmission {
+                line: self.line,
+                packet,
+            });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{self, Cursor};
+
+    /// Gives its bytes, then fails.
+    struct Failing<'a>(&'a [u8]);
+
+    impl Read for Failing<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::other("disconnected"));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    fn summary(transmission: Transmission) -> (usize, std::result::Result<u64, String>) {
+        let value = transmission
+            .packet
+            .map(|packet| packet.checked_value().unwrap())
+            .map_err(|err| err.to_string());
+        (transmission.line, value)
+    }
+
+    #[test]
+    fn test_transmissions() {
+        let input = "C200B40A82\n04005AC33890\r\n\n8A004A801A80\nzz\n9C0141080250320F1802104A08";
+        let decoded: Vec<_> = transmissions(Cursor::new(input)).map(summary).collect();
+        assert_eq!(
+            decoded,
+            [
+                (1, Ok(3)),
+                (2, Ok(54)),
+                (
+                    4,
+                    Err(
+                        "day 16, line 4, column 11: bit 43: the transmission ends in the \
+                         middle of a packet"
+                            .to_string()
+                    )
+                ),
+                (
+                    5,
+                    Err("day 16, line 5, column 1: invalid hex character `z`".to_string())
+                ),
+                (6, Ok(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_error() {
+        let mut stream = transmissions(Failing(b"D2FE28\nC200B40A82\n"));
+        assert_eq!(summary(stream.next().unwrap()), (1, Ok(2021)));
+        assert_eq!(summary(stream.next().unwrap()), (2, Ok(3)));
+        assert_eq!(
+            summary(stream.next().unwrap()),
+            (
+                3,
+                Err("day 16: cannot read the transmissions: disconnected".to_string())
+            )
+        );
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_not_utf8() {
+        let mut stream = transmissions(Cursor::new(b"D2\xFFE28\nD2FE28\n"));
+        assert_eq!(
+            summary(stream.next().unwrap()),
+            (
+                1,
+                Err("day 16, line 1, column 3: invalid hex character `\u{FFFD}`".to_string())
+            )
+        );
+        assert_eq!(summary(stream.next().unwrap()), (2, Ok(2021)));
+    }
+
+    #[test]
+    fn test_column_after_wide_indent() {
+        let mut stream = transmissions(Cursor::new("\u{3000}\u{3000}D2FG28\n"));
+        assert_eq!(
+            summary(stream.next().unwrap()),
+            (
+                1,
+                Err("day 16, line 1, column 6: invalid hex character `G`".to_string())
+            )
+        );
+    }
+}
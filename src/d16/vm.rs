@@ -0,0 +1,252 @@
+use std::convert::Infallible;
+use std::fmt::{self, Display};
+
+use super::{Operator, Overflow, Packet, PacketType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// Pushes a literal value.
+    Push(u64),
+    /// Stands for a literal too wide for the machine, and fails when run.
+    Wide,
+    /// Replaces the top `n` values with the sum, product, minimum or maximum of them.
+    Apply(Operator, usize),
+    /// Replaces the top two values with 1 if they compare as the operator says, else 0.
+    Compare(Operator),
+}
+
+/// A packet tree flattened into instructions for a stack machine, in post-order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    pub code: Vec<Instruction>,
+    /// For each instruction, the position in pre-order of the packet it came from.
+    packets: Vec<usize>,
+}
+
+/// Compiles `packet` into instructions, operands before their operator.
+pub fn compile(packet: &Packet) -> Program {
+    let mut program = Program {
+        code: Vec::new(),
+        packets: Vec::new(),
+    };
+    let compiled = packet.fold(|packet, position, _| {
+        let instruction = match &packet.kind {
+            PacketType::Literal(value) => Instruction::Push(*value),
+            PacketType::WideLiteral(_) => Instruction::Wide,
+            PacketType::Operator(operator, _) if operator.is_comparison() => {
+                Instruction::Compare(*operator)
+            }
+            PacketType::Operator(operator, subpackets) => {
+                Instruction::Apply(*operator, subpackets.len())
+            }
+        };
+        program.code.push(instruction);
+        program.packets.push(position);
+        Ok::<_, Infallible>(())
+    });
+    match compiled {
+        Ok(()) => program,
+        Err(never) => match never {},
+    }
+}
+
+impl Program {
+    /// Runs the program, giving the same value as [`Packet::checked_value`], or the same
+    /// overflow.
+    pub fn run(&self) -> Result<u64, Overflow> {
+        let mut stack: Vec<u64> = Vec::new();
+        for (instruction, &packet) in self.code.iter().zip(&self.packets) {
+            let (operator, arity) = match *instruction {
+                Instruction::Push(value) => {
+                    stack.push(value);
+                    continue;
+                }
+                Instruction::Wide => {
+                    return Err(Overflow {
+                        packet,
+                        operator: None,
+                    })
+                }
+                Instruction::Apply(operator, arity) => (operator, arity),
+                Instruction::Compare(operator) => (operator, 2),
+            };
+            let operands = stack.split_off(stack.len() - arity);
+            let value = operator.checked_apply(&operands).ok_or(Overflow {
+                packet,
+                operator: Some(operator),
+            })?;
+            stack.push(value);
+        }
+        Ok(stack.pop().unwrap())
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Push(value) => write!(f, "push {}", value),
+            Instruction::Wide => write!(f, "push wide"),
+            Instruction::Apply(operator, arity) => write!(f, "{} {}", operator.name(), arity),
+            Instruction::Compare(operator) => write!(f, "{}", operator.name()),
+        }
+    }
+}
+
+/// One instruction per line, with its index and the packet it came from.
+impl Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.code.len().saturating_sub(1).to_string().len();
+        self.code
+            .iter()
+            .zip(&self.packets)
+            .enumerate()
+            .try_for_each(|(i, (instruction, packet))| {
+                writeln!(
+                    f,
+                    "{:>width$}  {:<24} ; packet {}",
+                    i,
+                    instruction.to_string(),
+                    packet,
+                    width = width
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bits::BitWriter;
+    use super::super::compile::{self as expression, Versions};
+    use super::super::{p1, p2, parse_transmission, parse_with_layout, LengthType};
+    use super::*;
+
+    const PROBLEM_INPUT: &str = include_str!("../inputs/d16.txt");
+
+    fn run(packet: &Packet) -> Result<u64, Overflow> {
+        compile(packet).run()
+    }
+
+    #[test]
+    fn test_listing() {
+        let packet = expression::compile("1 + 2*3 + (4 > 5)", Versions::Fixed(0)).unwrap();
+        assert_eq!(
+            compile(&packet).to_string(),
+            "0  push 1                   ; packet 1
+1  push 2                   ; packet 3
+2  push 3                   ; packet 4
+3  product 2                ; packet 2
+4  push 4                   ; packet 6
+5  push 5                   ; packet 7
+6  gt                       ; packet 5
+7  sum 3                    ; packet 0
+"
+        );
+        assert_eq!(run(&packet), Ok(7));
+    }
+
+    #[test]
+    fn test_same_as_tree() {
+        let examples = [
+            "C200B40A82",
+            "04005AC33890",
+            "880086C3E88112",
+            "CE00C43D881120",
+            "D8005AC2A8F0",
+            "F600BC2D8F",
+            "9C005AC2F8F0",
+            "9C0141080250320F1802104A08",
+            PROBLEM_INPUT,
+        ];
+        for hex in examples.iter() {
+            let packet = parse_transmission(hex).unwrap();
+            assert_eq!(run(&packet), packet.checked_value());
+        }
+        assert_eq!(
+            run(&parse_transmission(PROBLEM_INPUT).unwrap()),
+            Ok(1015320896946)
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let source = format!("1 + {} * 2", u64::MAX);
+        let packet = expression::compile(&source, Versions::Fixed(0)).unwrap();
+        assert_eq!(run(&packet), packet.checked_value());
+        assert_eq!(
+            run(&packet),
+            Err(Overflow {
+                packet: 2,
+                operator: Some(Operator::Product)
+            })
+        );
+
+        let wide = expression::compile("(min 1 (max 2 18446744073709551616))", Versions::Fixed(0));
+        let wide = wide.unwrap();
+        assert_eq!(run(&wide), wide.checked_value());
+
+        // whichever is evaluated first is reported, as by `checked_value`
+        let wide = "18446744073709551616";
+        let source = format!("(sum (product {} 2) {})", u64::MAX, wide);
+        let packet = expression::compile(&source, Versions::Fixed(0)).unwrap();
+        assert_eq!(run(&packet), packet.checked_value());
+        assert_eq!(
+            run(&packet),
+            Err(Overflow {
+                packet: 1,
+                operator: Some(Operator::Product)
+            })
+        );
+        let source = format!("(sum {} (product {} 2))", wide, u64::MAX);
+        let packet = expression::compile(&source, Versions::Fixed(0)).unwrap();
+        assert_eq!(run(&packet), packet.checked_value());
+        assert_eq!(
+            run(&packet),
+            Err(Overflow {
+                packet: 1,
+                operator: None
+            })
+        );
+        assert_eq!(
+            compile(&packet).to_string(),
+            "0  push wide                ; packet 1
+1  push 18446744073709551615 ; packet 3
+2  push 2                   ; packet 4
+3  product 2                ; packet 2
+4  sum 2                    ; packet 0
+"
+        );
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        // 20000 levels of single-subpacket operators of length type 1, then a literal 1
+        let mut writer = BitWriter::new();
+        for n in 0..20000 {
+            let operator = if n % 2 == 0 {
+                Operator::Sum
+            } else {
+                Operator::Max
+            };
+            writer.write_bits(1, 3);
+            writer.write_bits(operator.type_id() as u64, 3);
+            writer.write_bits(LengthType::Count.id() as u64, 1);
+            writer.write_bits(1, LengthType::Count.field_width());
+        }
+        writer.write_bits(2, 3);
+        writer.write_bits(4, 3);
+        writer.write_bits(0b00001, 5);
+        let (bytes, _) = writer.into_bytes();
+        let hex: String = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+
+        assert_eq!(p1(&hex), Ok(20002));
+        assert_eq!(p2(&hex), Ok(1));
+        let (packet, layouts) = parse_with_layout(&hex).unwrap();
+        assert_eq!(layouts.len(), 20001);
+        assert_eq!(layouts[20000].offset, 20000 * 18);
+        assert_eq!(packet.big_value(), 1u64.into());
+
+        let program = compile(&packet);
+        assert_eq!(program.code.len(), 20001);
+        assert_eq!(program.run(), Ok(1));
+    }
+}
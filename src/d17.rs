@@ -1,87 +1,227 @@
 use std::ops::RangeInclusive;
 
-pub fn p1(input: (RangeInclusive<i32>, RangeInclusive<i32>)) -> i32 {
-    let maximum_y_drop = input.1.start();
-    maximum_y_drop * (maximum_y_drop + 1) / 2
-}
-
-pub fn p2(input: (RangeInclusive<i32>, RangeInclusive<i32>)) -> i32 {
-    let input = BoundingBox {
-        x: input.0,
-        y: input.1,
-    };
-
-    let min_x = *input.x.start();
-    let max_x = *input.x.end();
-    let min_y = *input.y.start();
-    let max_y = *input.y.end();
-    // compute upper bound of steps from maximum y drop
-    // the largest step count gives the tallest arc
-    //
-    // collect the intervals of "accuracy" for each step count
-    let intervals: Vec<_> = (1..=(-min_y + 1) * 2)
-        .map(|steps| {
-            // solve bounds for yv
-            // n / 2 * (yv - n + 1) is in (min_y, max_y)
-            // (slightly easier since yv can be negative)
-
-            // 2 / n * m_y + n - 1 ><= yv
-            let yv_min =
-                ((2 * min_y + steps * steps - steps) as f64 / 2.0 / steps as f64).ceil() as i32;
-            let yv_max =
-                ((2 * max_y + steps * steps - steps) as f64 / 2.0 / steps as f64).floor() as i32;
-
-            // solve bounds for xv
-            // simple case: n / 2 * (xv - n + 1) is in (min_x, max_x)
-
-            // 2 / n * m_x + n - 1 ><= xv
-            let xv_min =
-                ((2 * min_x + steps * steps - steps) as f64 / 2.0 / steps as f64).ceil() as i32;
-            let maybe_xv_max =
-                ((2 * max_x + steps * steps - steps) as f64 / 2.0 / steps as f64).floor() as i32;
-
-            // Special case when xv falls to zero and stays there (since it can't fall to negatives)
-            // (xv * xv + xv) / 2 in minmax
-            // Solve quadratic
-            let special_xv_min = (-1 + (1.0 + 8.0 * min_x as f64).sqrt().ceil() as i32) / 2;
-            let special_xv_max = (-1 + (1.0 + 8.0 * max_x as f64).sqrt().floor() as i32) / 2;
-
-            // exclude velocities that overshoot and "curve back left"
-            let xv_max = maybe_xv_max.min(2 * max_x / steps);
-
-            let x_range = if xv_min <= xv_max || special_xv_max > steps {
-                xv_min..=xv_max
-            } else {
-                special_xv_min..=special_xv_max
+use crate::error::{parse_number, split_once, Error, Result};
+use crate::solution::{Answer, Solution};
+
+pub mod probe;
+pub mod render;
+
+const DAY: u8 = 17;
+
+fn parse_range(input: &str, range: &str, axis: &str) -> Result<RangeInclusive<i32>> {
+    let range = range.strip_prefix(axis).ok_or_else(|| {
+        Error::at(
+            DAY,
+            input,
+            range,
+            format!("expected `{}` before the range", axis),
+        )
+    })?;
+    let (start, end) = split_once(DAY, input, range, "..")?;
+    Ok(parse_number(DAY, input, start)?..=parse_number(DAY, input, end)?)
+}
+
+pub fn parse_input(input: &str) -> Result<BoundingBox> {
+    let area = input.trim();
+    let area = area.strip_prefix("target area: ").ok_or_else(|| {
+        Error::at(
+            DAY,
+            input,
+            area,
+            "expected the line to start with `target area: `",
+        )
+    })?;
+    let (x, y) = split_once(DAY, input, area, ", ")?;
+
+    Ok(BoundingBox::new(
+        parse_range(input, x, "x=")?,
+        parse_range(input, y, "y=")?,
+    ))
+}
+
+/// Where the probe is after `steps` steps along x, launched at `vx`: drag slows it down
+/// until it stops.
+fn x_after(vx: i32, steps: i32) -> i32 {
+    let moving = steps.min(vx.abs());
+    vx.signum() * (moving * vx.abs() - moving * (moving - 1) / 2)
+}
+
+/// Where the probe is after `steps` steps along y, launched at `vy`.
+fn y_after(vy: i32, steps: i32) -> i32 {
+    steps * vy - steps * (steps - 1) / 2
+}
+
+/// Fails if infinitely many launch velocities hit `target`.
+///
+/// A probe launched upwards at `vy` is back at `y = 0` after `2 * vy + 1` steps, so when
+/// the target spans `y = 0` and the probe can stop above it, any `vy` high enough hits.
+fn check_bounded(target: &BoundingBox) -> Result<()> {
+    let stops_inside = (*target.x.start().min(&0)..=*target.x.end().max(&0))
+        .any(|vx| target.x.contains(&x_after(vx, vx.abs())));
+    if target.y.contains(&0) && stops_inside {
+        return Err(Error::new(
+            DAY,
+            "infinitely many launch velocities reach the target",
+        ));
+    }
+    Ok(())
+}
+
+/// The launch velocities that can possibly hit `target`, which [`check_bounded`] must
+/// accept: any other overshoots it on the first step, or moves away from it for good.
+fn velocity_bounds(target: &BoundingBox) -> BoundingBox {
+    let (min_x, max_x) = (*target.x.start(), *target.x.end());
+    let (min_y, max_y) = (*target.y.start(), *target.y.end());
+    // coming down, the probe passes every height it had going up, then one below 0 per
+    // unit of launch speed
+    let mut max_vy = min_y.abs().max(max_y.abs());
+    if target.y.contains(&0) {
+        // the probe cannot stop above the target, so it hits while still moving sideways,
+        // after `n` steps with `n * (n + 1) / 2` at most the distance to the far side; the
+        // height then is `n * vy - n * (n - 1) / 2`, at most `max_y`
+        let far = min_x.abs().max(max_x.abs());
+        let steps = (1..)
+            .take_while(|n| n * (n + 1) / 2 <= far)
+            .last()
+            .unwrap_or(0);
+        max_vy = max_vy.max(max_y + steps);
+    }
+    BoundingBox::new(min_x.min(0)..=max_x.max(0), min_y.min(0)..=max_vy)
+}
+
+/// A launch and the highest point of its trajectory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shot {
+    pub velocity: (i32, i32),
+    /// The first position at the highest `y` reached, the launcher itself if the probe
+    /// never rises.
+    pub apex: (i32, i32),
+}
+
+impl Shot {
+    fn new(velocity: (i32, i32)) -> Self {
+        let steps = velocity.1.max(0);
+        Shot {
+            velocity,
+            apex: (x_after(velocity.0, steps), y_after(velocity.1, steps)),
+        }
+    }
+}
+
+/// The shot that goes highest while still being inside `target` after some step, with
+/// the slowest horizontal speed among those that do. Every position can be hit on the
+/// first step, so this fails only for an empty target, or one that shots of any height
+/// hit.
+///
+/// The target may be on either side of the launcher, above or below it, or around it.
+pub fn highest_shot(target: &BoundingBox) -> Result<Shot> {
+    check_bounded(target)?;
+    let bounds = velocity_bounds(target);
+    let mut vxs: Vec<i32> = bounds.x.clone().collect();
+    vxs.sort_by_key(|vx| (vx.abs(), *vx));
+
+    let highest = bounds.y.clone().rev().find_map(|vy| {
+        // the steps that end in the target's rows, until the probe falls below them
+        let steps: Vec<i32> = (1..)
+            .map(|steps| (steps, y_after(vy, steps)))
+            .take_while(|&(steps, y)| steps <= vy || y >= *target.y.start())
+            .filter(|(_, y)| target.y.contains(y))
+            .map(|(steps, _)| steps)
+            .collect();
+        vxs.iter()
+            .find(|&&vx| {
+                steps
+                    .iter()
+                    .any(|&steps| target.x.contains(&x_after(vx, steps)))
+            })
+            .map(|&vx| Shot::new((vx, vy)))
+    });
+    highest.ok_or_else(|| Error::new(DAY, "no launch velocity reaches the target"))
+}
+
+pub fn p1(target: &BoundingBox) -> Result<i32> {
+    Ok(highest_shot(target)?.apex.1)
+}
+
+pub fn p2(target: &BoundingBox) -> Result<i32> {
+    if *target.x.end() < 0 {
+        return p2(&target.mirrored());
+    }
+    // the ranges below need the target below the launcher and to one side of it
+    if *target.x.start() <= 0 || *target.y.end() >= 0 {
+        return Ok(probe::hitting_velocities(target)?.len() as i32);
+    }
+    let (min_y, max_y) = (*target.y.start(), *target.y.end());
+    // a probe launched upwards at `vy` is back at `y = 0` after `2 * vy + 1` steps, then
+    // falls below the target on the next one unless `vy < -min_y`
+    let max_steps = 2 * -min_y;
+
+    let mut count = 0;
+    for vx in 1..=*target.x.end() {
+        // for each step that ends in the target's columns, the launch speeds `vy` that end
+        // it in the target's rows: `n * vy - n * (n - 1) / 2` between `min_y` and `max_y`.
+        // Both bounds grow with `n`, so the ranges come sorted and overlapping ones follow
+        // each other.
+        let ranges = (1..=max_steps)
+            .filter(|&steps| target.x.contains(&x_after(vx, steps)))
+            .map(|n| {
+                let vys = |y: i32| 2 * y + n * n - n;
+                (ceil_div(vys(min_y), 2 * n), floor_div(vys(max_y), 2 * n))
+            })
+            .filter(|(low, high)| low <= high);
+
+        let mut merged: Option<(i32, i32)> = None;
+        for (low, high) in ranges {
+            merged = match merged {
+                Some((start, end)) if low <= end + 1 => Some((start, end.max(high))),
+                Some((start, end)) => {
+                    count += end - start + 1;
+                    Some((low, high))
+                }
+                None => Some((low, high)),
             };
-            let y_range = yv_min..=yv_max;
-            // The rectangular interval
-            BoundingBox {
-                x: x_range,
-                y: y_range,
-            }
-        })
-        .collect();
-
-    // compute the area of unique regions in the rectangles
-    // the data guarantees that every rectangle overlap is between consecutive elements
-    // (i.e. if you are in the target for N steps using a given velocity, it's always N consecutive steps)
-    // furthermore, if more than two rectangles {R1, R2, R3, ...} intersect, the intersection R1 & R2
-    // is a superset of the intersection R1 & (R3 | R4 | ...)
-    // (this follows from the first guarantee)
-    intervals[0].area()
-        + intervals
-            .windows(2)
-            .map(|window| window[1].area() - window[1].intersection(&window[0]).area())
-            .sum::<i32>()
+        }
+        count += merged.map_or(0, |(start, end)| end - start + 1);
+    }
+    Ok(count)
+}
+
+fn floor_div(a: i32, b: i32) -> i32 {
+    a.div_euclid(b)
+}
+
+fn ceil_div(a: i32, b: i32) -> i32 {
+    -(-a).div_euclid(b)
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BoundingBox {
     x: RangeInclusive<i32>,
     y: RangeInclusive<i32>,
 }
 
 impl BoundingBox {
+    pub fn new(x: RangeInclusive<i32>, y: RangeInclusive<i32>) -> Self {
+        BoundingBox { x, y }
+    }
+
+    pub fn x(&self) -> &RangeInclusive<i32> {
+        &self.x
+    }
+
+    pub fn y(&self) -> &RangeInclusive<i32> {
+        &self.y
+    }
+
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        self.x.contains(&x) && self.y.contains(&y)
+    }
+
+    /// The box reflected across `x = 0`.
+    pub fn mirrored(&self) -> BoundingBox {
+        BoundingBox::new(-self.x.end()..=-self.x.start(), self.y.clone())
+    }
+
     pub fn width(&self) -> i32 {
         0.max(self.x.end() - self.x.start() + 1)
     }
@@ -102,21 +242,143 @@ impl BoundingBox {
     }
 }
 
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = DAY;
+    type Input = BoundingBox;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(target: &Self::Input) -> Result<Answer> {
+        Ok(p1(target)?.into())
+    }
+
+    fn part2(target: &Self::Input) -> Result<Answer> {
+        Ok(p2(target)?.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const PROBLEM_INPUT: (RangeInclusive<i32>, RangeInclusive<i32>) = (269..=292, -68..=-44);
-    const PROBLEM_P1_ANSWER: i32 = 0;
-    const PROBLEM_P2_ANSWER: i32 = 0;
+    const EXAMPLE_INPUT: &str = "target area: x=20..30, y=-10..-5";
+    const PROBLEM_INPUT: &str = include_str!("inputs/d17.txt");
+
+    const EXAMPLE_P1_ANSWER: i32 = 45;
+    const EXAMPLE_P2_ANSWER: i32 = 112;
+    const PROBLEM_P1_ANSWER: i32 = 2278;
+    const PROBLEM_P2_ANSWER: i32 = 996;
+
+    fn target(input: &str) -> BoundingBox {
+        parse_input(input).unwrap()
+    }
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(
+            parse_input(PROBLEM_INPUT),
+            Ok(BoundingBox::new(269..=292, -68..=-44))
+        );
+
+        let err = parse_input("target area: x=20..30, z=-10..-5").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 17, line 1, column 24: expected `y=` before the range"
+        );
+    }
+
+    #[test]
+    fn test_highest_shot() {
+        let example = target(EXAMPLE_INPUT);
+        assert_eq!(
+            highest_shot(&example),
+            Ok(Shot {
+                velocity: (6, 9),
+                apex: (21, 45)
+            })
+        );
+        assert_eq!(
+            highest_shot(&example.mirrored()),
+            Ok(Shot {
+                velocity: (-6, 9),
+                apex: (-21, 45)
+            })
+        );
+        // around the launcher's height, but with no stop above it: hit on the way down
+        // after 9 steps, while still moving sideways
+        assert_eq!(
+            highest_shot(&target("target area: x=54..54, y=-1..1")),
+            Ok(Shot {
+                velocity: (10, 4),
+                apex: (34, 10)
+            })
+        );
+        assert_eq!(
+            highest_shot(&target("target area: x=20..30, y=5..10")),
+            Ok(Shot {
+                velocity: (6, 10),
+                apex: (21, 55)
+            })
+        );
+        // straight up through the launcher's column
+        assert_eq!(
+            highest_shot(&target("target area: x=-2..2, y=3..4")),
+            Ok(Shot {
+                velocity: (0, 4),
+                apex: (0, 10)
+            })
+        );
+        assert!(highest_shot(&target("target area: x=30..20, y=-10..-5")).is_err());
+    }
+
+    #[test]
+    fn test_example() {
+        let example = target(EXAMPLE_INPUT);
+        assert_eq!(p1(&example), Ok(EXAMPLE_P1_ANSWER));
+        assert_eq!(p2(&example), Ok(EXAMPLE_P2_ANSWER));
+        assert_eq!(p2(&example.mirrored()), Ok(EXAMPLE_P2_ANSWER));
+    }
+
+    #[test]
+    fn test_stopped_inside() {
+        // slow shots stop inside the target long before they fall to its rows
+        let target = target("target area: x=1..3, y=-15..-15");
+        assert_eq!(p2(&target), Ok(17));
+        assert_eq!(
+            p2(&target),
+            Ok(probe::hitting_velocities(&target).unwrap().len() as i32)
+        );
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1(&target("target area: x=30..20, y=-10..-5")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 17: no launch velocity reaches the target"
+        );
+
+        // every `(6, vy)` stops at `x = 21` and comes back down through `y = 0`
+        let around = target("target area: x=20..30, y=-5..5");
+        let err = p1(&around).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 17: infinitely many launch velocities reach the target"
+        );
+        assert_eq!(highest_shot(&around), Err(err));
+    }
 
     #[test]
     fn test_problem_p1() {
-        assert_eq!(p1(PROBLEM_INPUT), PROBLEM_P1_ANSWER);
+        assert_eq!(p1(&target(PROBLEM_INPUT)), Ok(PROBLEM_P1_ANSWER));
     }
 
     #[test]
     fn test_problem_p2() {
-        assert_eq!(p2(PROBLEM_INPUT), PROBLEM_P2_ANSWER);
+        assert_eq!(p2(&target(PROBLEM_INPUT)), Ok(PROBLEM_P2_ANSWER));
     }
 }
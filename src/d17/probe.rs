@@ -0,0 +1,195 @@
+use super::{check_bounded, velocity_bounds, BoundingBox};
+use crate::error::Result;
+
+/// A probe in flight, stepped as the puzzle describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Probe {
+    pub position: (i32, i32),
+    pub velocity: (i32, i32),
+}
+
+impl Probe {
+    /// A probe at the launcher.
+    pub fn launch(velocity: (i32, i32)) -> Self {
+        Probe {
+            position: (0, 0),
+            velocity,
+        }
+    }
+
+    /// Moves the probe, then applies drag and gravity.
+    pub fn step(&mut self) {
+        self.position.0 += self.velocity.0;
+        self.position.1 += self.velocity.1;
+        self.velocity.0 -= self.velocity.0.signum();
+        self.velocity.1 -= 1;
+    }
+
+    /// Whether the probe can no longer end a step in `target`: it is below it and not
+    /// rising, or past it or stopped beside it horizontally.
+    fn missed(&self, target: &BoundingBox) -> bool {
+        let (x, y) = self.position;
+        let (vx, vy) = self.velocity;
+        (y < *target.y.start() && vy <= 0)
+            || (vx >= 0 && x > *target.x.end())
+            || (vx <= 0 && x < *target.x.start())
+    }
+}
+
+/// The positions of a probe after each step, up to hitting the target or missing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flight {
+    pub velocity: (i32, i32),
+    /// The position after each step, the launcher excluded.
+    pub positions: Vec<(i32, i32)>,
+    /// The index in `positions` of the first one inside the target, where the flight ends.
+    pub hit: Option<usize>,
+}
+
+impl Flight {
+    pub fn is_hit(&self) -> bool {
+        self.hit.is_some()
+    }
+}
+
+/// Flies a probe launched at `velocity` until it is inside `target` or cannot get there.
+pub fn simulate(target: &BoundingBox, velocity: (i32, i32)) -> Flight {
+    let mut probe = Probe::launch(velocity);
+    let mut positions = Vec::new();
+    loop {
+        probe.step();
+        positions.push(probe.position);
+        if target.contains(probe.position) {
+            let hit = Some(positions.len() - 1);
+            return Flight {
+                velocity,
+                positions,
+                hit,
+            };
+        }
+        if probe.missed(target) {
+            return Flight {
+                velocity,
+                positions,
+                hit: None,
+            };
+        }
+    }
+}
+
+/// Every launch velocity that puts the probe inside `target` after some step, by `x`
+/// then `y`. Fails if there are infinitely many.
+pub fn hitting_velocities(target: &BoundingBox) -> Result<Vec<(i32, i32)>> {
+    check_bounded(target)?;
+    let bounds = velocity_bounds(target);
+    Ok(bounds
+        .x
+        .clone()
+        .flat_map(|vx| bounds.y.clone().map(move |vy| (vx, vy)))
+        .filter(|&velocity| simulate(target, velocity).is_hit())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{highest_shot, p2, parse_input};
+    use super::*;
+
+    fn target(input: &str) -> BoundingBox {
+        parse_input(input).unwrap()
+    }
+
+    #[test]
+    fn test_simulate() {
+        let example = target("target area: x=20..30, y=-10..-5");
+        let flight = simulate(&example, (7, 2));
+        assert_eq!(
+            flight.positions,
+            [
+                (7, 2),
+                (13, 3),
+                (18, 3),
+                (22, 2),
+                (25, 0),
+                (27, -3),
+                (28, -7)
+            ]
+        );
+        assert_eq!(flight.hit, Some(6));
+
+        assert_eq!(simulate(&example, (9, 0)).hit, Some(3));
+        // falls through the target between two steps
+        let flight = simulate(&example, (17, -4));
+        assert!(!flight.is_hit());
+        assert_eq!(flight.positions, [(17, -4), (33, -9)]);
+    }
+
+    #[test]
+    fn test_hitting_velocities() {
+        let example = target("target area: x=20..30, y=-10..-5");
+        let velocities = hitting_velocities(&example).unwrap();
+        for velocity in [(23, -10), (6, 9), (7, -1), (30, -5), (6, 0)].iter() {
+            assert!(velocities.contains(velocity), "{:?}", velocity);
+        }
+        assert!(!velocities.contains(&(17, -4)));
+        assert_eq!(velocities.len(), 112);
+    }
+
+    #[test]
+    fn test_same_as_analytic_count() {
+        let mut targets = vec![
+            target("target area: x=20..30, y=-10..-5"),
+            target(include_str!("../inputs/d17.txt")),
+        ];
+        for (x1, x2) in (1..=12).flat_map(|x1| (x1..=12).map(move |x2| (x1, x2))) {
+            for (y1, y2) in (-15..=-1).flat_map(|y1| (y1..=-1).map(move |y2| (y1, y2))) {
+                let target = BoundingBox::new(x1..=x2, y1..=y2);
+                targets.push(target.mirrored());
+                targets.push(target);
+            }
+        }
+        for target in &targets {
+            assert_eq!(
+                hitting_velocities(target).map(|velocities| velocities.len() as i32),
+                p2(target),
+                "{:?}",
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn test_any_target() {
+        for input in [
+            "target area: x=54..54, y=-1..1",
+            "target area: x=20..30, y=5..10",
+            "target area: x=-2..2, y=3..4",
+            "target area: x=-5..12, y=-8..-1",
+        ]
+        .iter()
+        {
+            let target = target(input);
+            let velocities = hitting_velocities(&target).unwrap();
+            let highest = highest_shot(&target).unwrap();
+            assert_eq!(
+                velocities.iter().map(|velocity| velocity.1).max(),
+                Some(highest.velocity.1),
+                "{}",
+                input
+            );
+            assert!(velocities.contains(&highest.velocity));
+        }
+        assert_eq!(
+            hitting_velocities(&target("target area: x=54..54, y=-1..1")).map(|v| v.len()),
+            Ok(5)
+        );
+
+        // every `(6, vy)` stops at `x = 21` and comes back down through `y = 0`
+        let around = target("target area: x=20..30, y=-5..5");
+        assert_eq!(
+            hitting_velocities(&around).unwrap_err().reason,
+            "infinitely many launch velocities reach the target"
+        );
+        assert!(p2(&around).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use super::probe::{simulate, Flight};
+use super::{BoundingBox, Shot};
+
+/// What to mark on top of the trajectory, besides the launcher, the probe and the target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Marks {
+    /// The highest point of the trajectory, as `^`, if the probe gets there.
+    pub apex: bool,
+    /// The first position inside the target, as `X`.
+    pub hit: bool,
+}
+
+/// Draws the flight of a probe launched at `velocity` the way the puzzle does: `S` for
+/// the launcher, `#` for the probe after each step and `T` for the target, `y` going up.
+///
+/// The grid is cropped to the launcher, the target and the positions the probe goes
+/// through before hitting the target or missing it for good.
+pub fn render(target: &BoundingBox, velocity: (i32, i32), marks: Marks) -> String {
+    let flight = simulate(target, velocity);
+    let apex = Shot::new(velocity).apex;
+    let hit = flight.hit.map(|i| flight.positions[i]);
+
+    let (min_x, max_x, min_y, max_y) = crop(target, &flight);
+    let mut drawing = String::new();
+    for y in (min_y..=max_y).rev() {
+        for x in min_x..=max_x {
+            let position = (x, y);
+            drawing.push(if position == (0, 0) {
+                'S'
+            } else if marks.hit && Some(position) == hit {
+                'X'
+            } else if marks.apex && position == apex && flight.positions.contains(&apex) {
+                '^'
+            } else if flight.positions.contains(&position) {
+                '#'
+            } else if target.contains(position) {
+                'T'
+            } else {
+                '.'
+            });
+        }
+        drawing.push('\n');
+    }
+    drawing
+}
+
+/// The smallest `x` and `y` ranges holding everything to draw.
+fn crop(target: &BoundingBox, flight: &Flight) -> (i32, i32, i32, i32) {
+    let corners = [
+        (0, 0),
+        (*target.x.start(), *target.y.start()),
+        (*target.x.end(), *target.y.end()),
+    ];
+    corners.iter().chain(&flight.positions).fold(
+        (i32::MAX, i32::MIN, i32::MAX, i32::MIN),
+        |(min_x, max_x, min_y, max_y), &(x, y)| {
+            (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_input;
+    use super::*;
+
+    fn example() -> BoundingBox {
+        parse_input("target area: x=20..30, y=-10..-5").unwrap()
+    }
+
+    #[test]
+    fn test_render_hit() {
+        assert_eq!(
+            render(&example(), (7, 2), Marks::default()),
+            ".............#....#............
+.......#..............#........
+...............................
+S........................#.....
+...............................
+...............................
+...........................#...
+...............................
+....................TTTTTTTTTTT
+....................TTTTTTTTTTT
+....................TTTTTTTT#TT
+....................TTTTTTTTTTT
+....................TTTTTTTTTTT
+....................TTTTTTTTTTT
+"
+        );
+        assert_eq!(
+            render(
+                &example(),
+                (7, 2),
+                Marks {
+                    apex: true,
+                    hit: true
+                }
+            ),
+            ".............^....#............
+.......#..............#........
+...............................
+S........................#.....
+...............................
+...............................
+...........................#...
+...............................
+....................TTTTTTTTTTT
+....................TTTTTTTTTTT
+....................TTTTTTTTXTT
+....................TTTTTTTTTTT
+....................TTTTTTTTTTT
+....................TTTTTTTTTTT
+"
+        );
+    }
+
+    #[test]
+    fn test_render_marks() {
+        let marks = Marks {
+            apex: true,
+            hit: true,
+        };
+        assert_eq!(
+            render(&example(), (17, -4), marks),
+            "S.................................
+..................................
+..................................
+..................................
+.................#................
+....................TTTTTTTTTTT...
+....................TTTTTTTTTTT...
+....................TTTTTTTTTTT...
+....................TTTTTTTTTTT...
+....................TTTTTTTTTTT..#
+....................TTTTTTTTTTT...
+"
+        );
+        // the target left of the launcher
+        let target = parse_input("target area: x=-4..-3, y=1..2").unwrap();
+        assert_eq!(
+            render(&target, (-3, 1), marks),
+            "TT...
+TX...
+....S
+"
+        );
+        // the target is hit on the way up, before the apex
+        let target = parse_input("target area: x=1..1, y=2..2").unwrap();
+        assert_eq!(
+            render(&target, (1, 2), marks),
+            ".X
+..
+S.
+"
+        );
+    }
+}
@@ -1,8 +1,17 @@
-use serde::Deserialize;
+use std::fmt::{self, Display};
+use std::iter::Sum;
 use std::ops::Add;
+use std::str::FromStr;
+use std::thread;
 
-#[derive(Debug, Deserialize, Clone)]
-#[serde(untagged)]
+use crate::error::{parse_number, Error, Result};
+use crate::solution::{Answer, Solution};
+
+pub mod flat;
+
+const DAY: u8 = 18;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SnailNumber {
     Literal(i32),
     Pair(Box<Self>, Box<Self>),
@@ -16,10 +25,11 @@ impl SnailNumber {
         }
     }
 
-    fn explode(&mut self, depth: usize) -> Option<(i32, i32)> {
+    /// Explodes the leftmost pair nested in four pairs, leaving `path` pointing at it.
+    fn explode(&mut self, path: &mut Vec<Side>) -> Option<Explosion> {
         match self {
             SnailNumber::Literal(_) => None,
-            SnailNumber::Pair(l, r) => match depth {
+            SnailNumber::Pair(l, r) => match path.len() {
                 4 => {
                     let a = match **l {
                         SnailNumber::Literal(val) => val,
@@ -31,17 +41,25 @@ impl SnailNumber {
                     };
                     *self = SnailNumber::Literal(0);
 
-                    Some((a, b))
+                    Some(Explosion {
+                        pair: (a, b),
+                        carry: (a, b),
+                    })
                 }
                 _ => {
-                    if let Some((a, b)) = l.explode(depth + 1) {
-                        r.absorb(true, b);
-                        return Some((a, 0));
+                    path.push(Side::Left);
+                    if let Some(mut explosion) = l.explode(path) {
+                        r.absorb(true, explosion.carry.1);
+                        explosion.carry.1 = 0;
+                        return Some(explosion);
                     }
-                    if let Some((a, b)) = r.explode(depth + 1) {
-                        r.absorb(false, a);
-                        return Some((0, b));
+                    *path.last_mut().unwrap() = Side::Right;
+                    if let Some(mut explosion) = r.explode(path) {
+                        l.absorb(false, explosion.carry.0);
+                        explosion.carry.0 = 0;
+                        return Some(explosion);
                     }
+                    path.pop();
 
                     None
                 }
@@ -59,30 +77,116 @@ impl SnailNumber {
         }
     }
 
-    fn split(&mut self) -> Option<()> {
+    /// Splits the leftmost value of 10 or more, leaving `path` pointing at it.
+    fn split(&mut self, path: &mut Vec<Side>) -> Option<i32> {
         match self {
             SnailNumber::Literal(val) => {
-                if *val >= 10 {
+                let val = *val;
+                if val >= 10 {
                     *self = SnailNumber::Pair(
-                        Box::new(SnailNumber::Literal((*val as f32 / 2.0).floor() as i32)),
-                        Box::new(SnailNumber::Literal((*val as f32 / 2.0).ceil() as i32)),
+                        Box::new(SnailNumber::Literal(val / 2)),
+                        Box::new(SnailNumber::Literal(val - val / 2)),
                     );
-                    Some(())
+                    Some(val)
                 } else {
                     None
                 }
             }
             SnailNumber::Pair(left, right) => {
-                if left.split().is_some() {
-                    return Some(());
+                path.push(Side::Left);
+                if let Some(val) = left.split(path) {
+                    return Some(val);
                 }
-                if right.split().is_some() {
-                    return Some(());
+                *path.last_mut().unwrap() = Side::Right;
+                if let Some(val) = right.split(path) {
+                    return Some(val);
                 }
+                path.pop();
                 None
             }
         }
     }
+
+    /// Applies the first reduction action that applies, if any, with `path` set to
+    /// where it happened.
+    fn reduce_once(&mut self, path: &mut Vec<Side>) -> Option<Action> {
+        path.clear();
+        if let Some(Explosion {
+            pair: (left, right),
+            ..
+        }) = self.explode(path)
+        {
+            return Some(Action::Explode { left, right });
+        }
+        path.clear();
+        self.split(path).map(|value| Action::Split { value })
+    }
+
+    /// Adds `rhs` without reducing, then yields every reduction step in order.
+    pub fn add_steps(self, rhs: Self) -> Reduction {
+        Reduction {
+            number: Self::Pair(Box::new(self), Box::new(rhs)),
+            path: Vec::new(),
+        }
+    }
+}
+
+struct Explosion {
+    /// The values of the pair that exploded.
+    pair: (i32, i32),
+    /// The values still to add to the nearest regular numbers on each side.
+    carry: (i32, i32),
+}
+
+/// Which child of a pair a path goes through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// A pair exploded, its values carried to the nearest regular numbers on each side.
+    Explode { left: i32, right: i32 },
+    /// A regular number was split into a pair.
+    Split { value: i32 },
+}
+
+/// One step of reducing a snailfish number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub action: Action,
+    /// From the root, the pair that exploded or the number that was split.
+    pub path: Vec<Side>,
+    /// The number after the step.
+    pub number: SnailNumber,
+}
+
+/// The steps of reducing a sum, see [`SnailNumber::add_steps`].
+pub struct Reduction {
+    number: SnailNumber,
+    path: Vec<Side>,
+}
+
+impl Reduction {
+    /// The number as reduced so far.
+    pub fn number(&self) -> &SnailNumber {
+        &self.number
+    }
+}
+
+impl Iterator for Reduction {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let action = self.number.reduce_once(&mut self.path)?;
+        Some(Step {
+            action,
+            path: self.path.clone(),
+            number: self.number.clone(),
+        })
+    }
 }
 
 impl Add for SnailNumber {
@@ -90,57 +194,429 @@ impl Add for SnailNumber {
 
     fn add(self, rhs: Self) -> Self::Output {
         let mut new_pair = Self::Pair(Box::new(self), Box::new(rhs));
+        let mut path = Vec::new();
+        while new_pair.reduce_once(&mut path).is_some() {}
+        new_pair
+    }
+}
 
-        // Reduce
-        loop {
-            if new_pair.explode(0).is_some() {
-                continue;
-            }
+/// Adds the numbers in order. The sum of no numbers is the regular number 0.
+impl Sum for SnailNumber {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(|sum, n| sum + n)
+            .unwrap_or(SnailNumber::Literal(0))
+    }
+}
 
-            if new_pair.split().is_some() {
-                continue;
-            }
+impl<'a> Sum<&'a SnailNumber> for SnailNumber {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.cloned().sum()
+    }
+}
 
-            break;
+impl SnailNumber {
+    /// Parses `line`, a slice of `input` that errors point into.
+    fn parse(input: &str, line: &str) -> Result<Self> {
+        let (number, rest) = parse_prefix(input, line)?;
+        match rest.chars().next() {
+            None => Ok(number),
+            Some(c) => Err(Error::at(
+                DAY,
+                input,
+                rest,
+                format!("unexpected `{}` after the number", c),
+            )),
         }
+    }
+}
 
-        new_pair
+/// Parses the number at the start of `text`, returning what follows it.
+fn parse_prefix<'a>(input: &str, text: &'a str) -> Result<(SnailNumber, &'a str)> {
+    match text.chars().next() {
+        Some('[') => {
+            let (left, rest) = parse_prefix(input, &text[1..])?;
+            let rest = expect(input, rest, ',')?;
+            let (right, rest) = parse_prefix(input, rest)?;
+            let rest = expect(input, rest, ']')?;
+            Ok((SnailNumber::Pair(Box::new(left), Box::new(right)), rest))
+        }
+        Some(c) if c.is_ascii_digit() => {
+            let end = text
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(text.len());
+            let value = parse_number(DAY, input, &text[..end])?;
+            Ok((SnailNumber::Literal(value), &text[end..]))
+        }
+        Some(c) => Err(Error::at(
+            DAY,
+            input,
+            text,
+            format!("expected `[` or a digit, found `{}`", c),
+        )),
+        None => Err(Error::at(DAY, input, text, "the number ends too early")),
+    }
+}
+
+/// Skips `c` at the start of `text`.
+fn expect<'a>(input: &str, text: &'a str, c: char) -> Result<&'a str> {
+    match text.chars().next() {
+        Some(found) if found == c => Ok(&text[1..]),
+        Some(found) => Err(Error::at(
+            DAY,
+            input,
+            text,
+            format!("expected `{}`, found `{}`", c, found),
+        )),
+        None => Err(Error::at(DAY, input, text, "the number ends too early")),
+    }
+}
+
+impl FromStr for SnailNumber {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        SnailNumber::parse(s, s)
+    }
+}
+
+/// The puzzle's notation, `[[1,2],3]`, which parses back to the same number.
+impl Display for SnailNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnailNumber::Literal(v) => write!(f, "{}", v),
+            SnailNumber::Pair(a, b) => write!(f, "[{},{}]", a, b),
+        }
     }
 }
 
-pub fn p1(input: &str) -> i32 {
-    let v: Vec<_> = input
+/// Fails if `line` has a pair nested inside four pairs. Reduction only explodes those
+/// right after an addition, so the numbers to add must not have any.
+fn check_reduced(input: &str, line: &str) -> Result<()> {
+    let mut depth = 0;
+    for (i, c) in line.char_indices() {
+        match c {
+            '[' if depth == 4 => {
+                return Err(Error::at(
+                    DAY,
+                    input,
+                    &line[i..],
+                    "a reduced number has no pair nested inside four pairs",
+                ))
+            }
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<SnailNumber>> {
+    input
         .trim()
         .lines()
-        .map(|l| serde_json::from_str::<SnailNumber>(l).unwrap())
-        .collect();
+        .map(|line| {
+            let number = SnailNumber::parse(input, line)?;
+            check_reduced(input, line)?;
+            Ok(number)
+        })
+        .collect()
+}
 
-    v.iter()
-        .skip(1)
-        .fold(v[0].clone(), |acc, nxt| acc + nxt.clone())
-        .magnitude()
+fn final_sum_magnitude(v: &[SnailNumber]) -> Result<i32> {
+    if v.is_empty() {
+        return Err(Error::new(DAY, "there are no numbers to add"));
+    }
+    Ok(v.iter().sum::<SnailNumber>().magnitude())
 }
 
-pub fn p2(input: &str) -> usize {
-    0
+/// The largest magnitude of `a + b` over ordered pairs of distinct numbers, `None` for
+/// fewer than two numbers.
+///
+/// With more than one thread, the first numbers of the pairs are dealt out between them.
+pub fn largest_pair_magnitude(numbers: &[SnailNumber], threads: usize) -> Option<i32> {
+    let largest_from = |first: usize, step: usize| {
+        (first..numbers.len())
+            .step_by(step)
+            .flat_map(|i| {
+                (0..numbers.len())
+                    .filter(move |&j| j != i)
+                    .map(move |j| (i, j))
+            })
+            .map(|(i, j)| (numbers[i].clone() + numbers[j].clone()).magnitude())
+            .max()
+    };
+    let threads = threads.clamp(1, numbers.len().max(1));
+    if threads == 1 {
+        return largest_from(0, 1);
+    }
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|first| scope.spawn(move || largest_from(first, threads)))
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .max()
+    })
+}
+
+fn largest_magnitude(numbers: &[SnailNumber]) -> Result<i32> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    largest_pair_magnitude(numbers, threads)
+        .ok_or_else(|| Error::new(DAY, "part 2 needs at least two numbers"))
+}
+
+pub fn p1(input: &str) -> Result<i32> {
+    final_sum_magnitude(&parse_input(input)?)
+}
+
+pub fn p2(input: &str) -> Result<i32> {
+    largest_magnitude(&parse_input(input)?)
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = DAY;
+    type Input = Vec<SnailNumber>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(numbers: &Self::Input) -> Result<Answer> {
+        Ok(final_sum_magnitude(numbers)?.into())
+    }
+
+    fn part2(numbers: &Self::Input) -> Result<Answer> {
+        Ok(largest_magnitude(numbers)?.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const EXAMPLE_INPUT: &str = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]
+";
     const PROBLEM_INPUT: &str = include_str!("inputs/d18.txt");
 
-    const PROBLEM_P1_ANSWER: i32 = 0;
-    const PROBLEM_P2_ANSWER: usize = 0;
+    const EXAMPLE_P1_ANSWER: i32 = 4140;
+    const PROBLEM_P1_ANSWER: i32 = 4365;
+    const EXAMPLE_P2_ANSWER: i32 = 3993;
+    const PROBLEM_P2_ANSWER: i32 = 4490;
+
+    fn number(s: &str) -> SnailNumber {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for line in PROBLEM_INPUT.lines().chain(EXAMPLE_INPUT.lines()) {
+            let n = number(line);
+            assert_eq!(n.to_string(), line);
+            assert_eq!(number(&n.to_string()), n);
+        }
+        assert_eq!(
+            number("[[1,2],3]"),
+            SnailNumber::Pair(
+                Box::new(SnailNumber::Pair(
+                    Box::new(SnailNumber::Literal(1)),
+                    Box::new(SnailNumber::Literal(2))
+                )),
+                Box::new(SnailNumber::Literal(3))
+            )
+        );
+    }
+
+    #[test]
+    fn test_add() {
+        let sum = number("[[[[4,3],4],4],[7,[[8,4],9]]]") + number("[1,1]");
+        assert_eq!(sum.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+
+        let total = parse_input(EXAMPLE_INPUT)
+            .unwrap()
+            .into_iter()
+            .reduce(|a, b| a + b)
+            .unwrap();
+        assert_eq!(
+            total.to_string(),
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]"
+        );
+    }
+
+    #[test]
+    fn test_reduction_steps() {
+        use Side::{Left as L, Right as R};
+
+        let mut reduction = number("[[[[4,3],4],4],[7,[[8,4],9]]]").add_steps(number("[1,1]"));
+        assert_eq!(
+            reduction.number().to_string(),
+            "[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]"
+        );
+        let steps: Vec<(Action, Vec<Side>, String)> = reduction
+            .by_ref()
+            .map(|step| (step.action, step.path, step.number.to_string()))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (
+                    Action::Explode { left: 4, right: 3 },
+                    vec![L, L, L, L],
+                    "[[[[0,7],4],[7,[[8,4],9]]],[1,1]]".to_string()
+                ),
+                (
+                    Action::Explode { left: 8, right: 4 },
+                    vec![L, R, R, L],
+                    "[[[[0,7],4],[15,[0,13]]],[1,1]]".to_string()
+                ),
+                (
+                    Action::Split { value: 15 },
+                    vec![L, R, L],
+                    "[[[[0,7],4],[[7,8],[0,13]]],[1,1]]".to_string()
+                ),
+                (
+                    Action::Split { value: 13 },
+                    vec![L, R, R, R],
+                    "[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]".to_string()
+                ),
+                (
+                    Action::Explode { left: 6, right: 7 },
+                    vec![L, R, R, R],
+                    "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            reduction.number().to_string(),
+            "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"
+        );
+    }
+
+    #[test]
+    fn test_trace_round_trip() {
+        let mut reduction = number("[[[[4,3],4],4],[7,[[8,4],9]]]").add_steps(number("[1,1]"));
+        let mut numbers = vec![reduction.number().clone()];
+        numbers.extend(reduction.by_ref().map(|step| step.number));
+        numbers.push(reduction.number().clone());
+        // the numbers before and between the steps nest pairs deeper than the input may
+        for n in &numbers {
+            assert_eq!(number(&n.to_string()), *n);
+        }
+        assert!(parse_input(&numbers[0].to_string()).is_err());
+    }
+
+    #[test]
+    fn test_magnitude() {
+        assert_eq!(number("[[1,2],[[3,4],5]]").magnitude(), 143);
+        assert_eq!(
+            number("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]").magnitude(),
+            3488
+        );
+    }
+
+    #[test]
+    fn test_example_p1() {
+        assert_eq!(p1(EXAMPLE_INPUT), Ok(EXAMPLE_P1_ANSWER));
+    }
+
+    #[test]
+    fn test_sum() {
+        let numbers = parse_input(EXAMPLE_INPUT).unwrap();
+        assert_eq!(
+            numbers.iter().sum::<SnailNumber>().to_string(),
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]"
+        );
+        assert_eq!(
+            numbers
+                .into_iter()
+                .rev()
+                .take(1)
+                .sum::<SnailNumber>()
+                .to_string(),
+            "[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]"
+        );
+        assert_eq!(
+            Vec::<SnailNumber>::new().into_iter().sum::<SnailNumber>(),
+            SnailNumber::Literal(0)
+        );
+    }
+
+    #[test]
+    fn test_example_p2() {
+        assert_eq!(p2(EXAMPLE_INPUT), Ok(EXAMPLE_P2_ANSWER));
+    }
+
+    #[test]
+    fn test_threads() {
+        let numbers = parse_input(PROBLEM_INPUT).unwrap();
+        for threads in [1, 3, 8, 500].iter() {
+            assert_eq!(
+                largest_pair_magnitude(&numbers, *threads),
+                Some(PROBLEM_P2_ANSWER)
+            );
+        }
+        assert_eq!(largest_pair_magnitude(&numbers[..1], 4), None);
+        assert_eq!(largest_pair_magnitude(&[], 4), None);
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("[1,2]\n[[1,2],3\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 18, line 2, column 9: the number ends too early"
+        );
+
+        let err = p1("[1,2]\n[[1;2],3]\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 18, line 2, column 4: expected `,`, found `;`"
+        );
+
+        let err = p1("[1,2]]\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 18, line 1, column 6: unexpected `]` after the number"
+        );
+
+        let err = p1("[1,2]\n[[[[[[1,2],3],4],5],6],7]\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 18, line 2, column 5: a reduced number has no pair nested inside four pairs"
+        );
+
+        let err = p1("[x,2]\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 18, line 1, column 2: expected `[` or a digit, found `x`"
+        );
+
+        let err = p1("").unwrap_err();
+        assert_eq!(err.reason, "there are no numbers to add");
+
+        let err = p2("[1,2]\n").unwrap_err();
+        assert_eq!(err.to_string(), "day 18: part 2 needs at least two numbers");
+    }
 
     #[test]
     fn test_problem_p1() {
-        assert_eq!(p1(PROBLEM_INPUT), PROBLEM_P1_ANSWER);
+        assert_eq!(p1(PROBLEM_INPUT), Ok(PROBLEM_P1_ANSWER));
     }
 
     #[test]
     fn test_problem_p2() {
-        assert_eq!(p2(PROBLEM_INPUT), PROBLEM_P2_ANSWER);
+        assert_eq!(p2(PROBLEM_INPUT), Ok(PROBLEM_P2_ANSWER));
     }
 }
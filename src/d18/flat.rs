@@ -0,0 +1,284 @@
+use std::convert::TryFrom;
+use std::ops::{Add, AddAssign};
+
+use super::{SnailNumber, DAY};
+use crate::error::{Error, Result};
+
+/// A regular number of a snailfish number with how many pairs it is nested in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Element {
+    pub depth: u8,
+    pub value: i32,
+}
+
+/// A snailfish number as its regular numbers from left to right, in a single vector.
+///
+/// Adding and reducing work in place, so summing into a reused number allocates only
+/// when the vector has to grow. Like the numbers of the homework, it is always reduced,
+/// and has at least one regular number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatNumber {
+    elements: Vec<Element>,
+}
+
+impl FlatNumber {
+    pub fn elements(&self) -> &[Element] {
+        &self.elements
+    }
+
+    /// Replaces this number by `other`, keeping the vector.
+    pub fn copy_from(&mut self, other: &FlatNumber) {
+        self.elements.clear();
+        self.elements.extend_from_slice(&other.elements);
+    }
+
+    pub fn magnitude(&self) -> i32 {
+        // combine the top two values while they are the two halves of a pair
+        let mut stack: Vec<Element> = Vec::with_capacity(8);
+        for &element in &self.elements {
+            stack.push(element);
+            while let [.., a, b] = stack[..] {
+                if a.depth != b.depth {
+                    break;
+                }
+                stack.truncate(stack.len() - 2);
+                stack.push(Element {
+                    depth: a.depth - 1,
+                    value: 3 * a.value + 2 * b.value,
+                });
+            }
+        }
+        stack[0].value
+    }
+
+    /// Explodes the leftmost pair nested in four pairs, whose values are then the only
+    /// ones at depth 5.
+    fn explode(&mut self) -> bool {
+        let i = match self.elements.iter().position(|e| e.depth > 4) {
+            Some(i) => i,
+            None => return false,
+        };
+        let Element { depth, value: left } = self.elements[i];
+        let right = self.elements[i + 1].value;
+        if i > 0 {
+            self.elements[i - 1].value += left;
+        }
+        if let Some(next) = self.elements.get_mut(i + 2) {
+            next.value += right;
+        }
+        self.elements[i] = Element {
+            depth: depth - 1,
+            value: 0,
+        };
+        self.elements.remove(i + 1);
+        true
+    }
+
+    /// Splits the leftmost value of 10 or more.
+    fn split(&mut self) -> bool {
+        let i = match self.elements.iter().position(|e| e.value >= 10) {
+            Some(i) => i,
+            None => return false,
+        };
+        let Element { depth, value } = self.elements[i];
+        self.elements[i] = Element {
+            depth: depth + 1,
+            value: value / 2,
+        };
+        self.elements.insert(
+            i + 1,
+            Element {
+                depth: depth + 1,
+                value: value - value / 2,
+            },
+        );
+        true
+    }
+
+    fn reduce(&mut self) {
+        while self.explode() || self.split() {}
+    }
+}
+
+impl AddAssign<&FlatNumber> for FlatNumber {
+    fn add_assign(&mut self, rhs: &FlatNumber) {
+        self.elements.extend_from_slice(&rhs.elements);
+        for element in &mut self.elements {
+            element.depth += 1;
+        }
+        self.reduce();
+    }
+}
+
+impl Add for FlatNumber {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += &rhs;
+        self
+    }
+}
+
+/// Fails for a number with a pair nested inside four pairs, which reducing after an
+/// addition could not explode.
+impl TryFrom<&SnailNumber> for FlatNumber {
+    type Error = Error;
+
+    fn try_from(number: &SnailNumber) -> Result<Self> {
+        fn flatten(number: &SnailNumber, depth: u8, elements: &mut Vec<Element>) {
+            match number {
+                SnailNumber::Literal(value) => elements.push(Element {
+                    depth,
+                    value: *value,
+                }),
+                SnailNumber::Pair(a, b) => {
+                    flatten(a, depth + 1, elements);
+                    flatten(b, depth + 1, elements);
+                }
+            }
+        }
+
+        let mut elements = Vec::new();
+        flatten(number, 0, &mut elements);
+        if elements.iter().any(|element| element.depth > 4) {
+            return Err(Error::new(
+                DAY,
+                "a reduced number has no pair nested inside four pairs",
+            ));
+        }
+        Ok(FlatNumber { elements })
+    }
+}
+
+impl From<&FlatNumber> for SnailNumber {
+    fn from(number: &FlatNumber) -> Self {
+        // the same pairing as `magnitude`, building the tree instead
+        let mut stack: Vec<(u8, SnailNumber)> = Vec::new();
+        for element in &number.elements {
+            let mut top = (element.depth, SnailNumber::Literal(element.value));
+            while let Some((depth, _)) = stack.last() {
+                if *depth != top.0 {
+                    break;
+                }
+                let (_, a) = stack.pop().unwrap();
+                top = (top.0 - 1, SnailNumber::Pair(Box::new(a), Box::new(top.1)));
+            }
+            stack.push(top);
+        }
+        stack.pop().unwrap().1
+    }
+}
+
+/// The magnitude of the sum of `numbers` in order, `None` if there are none.
+pub fn final_sum_magnitude(numbers: &[FlatNumber]) -> Option<i32> {
+    let (first, rest) = numbers.split_first()?;
+    let mut sum = first.clone();
+    for number in rest {
+        sum += number;
+    }
+    Some(sum.magnitude())
+}
+
+/// The largest magnitude of `a + b` over ordered pairs of distinct numbers, `None` for
+/// fewer than two numbers. Every sum is worked out in the same vector.
+pub fn largest_pair_magnitude(numbers: &[FlatNumber]) -> Option<i32> {
+    let mut sum = numbers.first()?.clone();
+    let mut largest = None;
+    for (i, a) in numbers.iter().enumerate() {
+        for (j, b) in numbers.iter().enumerate() {
+            if i != j {
+                sum.copy_from(a);
+                sum += b;
+                largest = largest.max(Some(sum.magnitude()));
+            }
+        }
+    }
+    largest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_input;
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]
+";
+    const PROBLEM_INPUT: &str = include_str!("../inputs/d18.txt");
+
+    fn flat(input: &str) -> Vec<FlatNumber> {
+        parse_input(input)
+            .unwrap()
+            .iter()
+            .map(|number| FlatNumber::try_from(number).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let flat = |number: &SnailNumber| FlatNumber::try_from(number).unwrap();
+        for number in parse_input(EXAMPLE_INPUT).unwrap() {
+            assert_eq!(SnailNumber::from(&flat(&number)), number);
+        }
+        let number: SnailNumber = "[[1,2],3]".parse().unwrap();
+        assert_eq!(
+            flat(&number).elements(),
+            [
+                Element { depth: 2, value: 1 },
+                Element { depth: 2, value: 2 },
+                Element { depth: 1, value: 3 },
+            ]
+        );
+        let literal = SnailNumber::Literal(7);
+        assert_eq!(SnailNumber::from(&flat(&literal)), literal);
+    }
+
+    #[test]
+    fn test_too_deep() {
+        let mut number: SnailNumber = "[1,2]".parse().unwrap();
+        for _ in 0..4 {
+            number = SnailNumber::Pair(Box::new(number), Box::new(SnailNumber::Literal(3)));
+        }
+        let err = FlatNumber::try_from(&number).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 18: a reduced number has no pair nested inside four pairs"
+        );
+    }
+
+    #[test]
+    fn test_same_as_tree() {
+        for input in [EXAMPLE_INPUT, PROBLEM_INPUT].iter() {
+            let trees = parse_input(input).unwrap();
+            let numbers = flat(input);
+
+            let sum = numbers.iter().cloned().reduce(|a, b| a + b).unwrap();
+            let tree_sum: SnailNumber = trees.iter().sum();
+            assert_eq!(SnailNumber::from(&sum), tree_sum);
+            assert_eq!(sum.magnitude(), tree_sum.magnitude());
+
+            assert_eq!(
+                largest_pair_magnitude(&numbers),
+                super::super::largest_pair_magnitude(&trees, 1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_answers() {
+        assert_eq!(final_sum_magnitude(&flat(EXAMPLE_INPUT)), Some(4140));
+        assert_eq!(largest_pair_magnitude(&flat(EXAMPLE_INPUT)), Some(3993));
+        assert_eq!(final_sum_magnitude(&flat(PROBLEM_INPUT)), Some(4365));
+        assert_eq!(largest_pair_magnitude(&flat(PROBLEM_INPUT)), Some(4490));
+        assert_eq!(final_sum_magnitude(&[]), None);
+        assert_eq!(largest_pair_magnitude(&flat("[1,2]")), None);
+    }
+}
@@ -1,40 +1,293 @@
-pub fn p1(input: &str) -> usize {
-    0
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{parse_number, Error, Result};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 19;
+
+/// How many beacons two scanners must both see to be aligned.
+const OVERLAP: usize = 12;
+
+pub type Point = [i32; 3];
+
+/// The beacons one scanner reports, relative to itself and in its own orientation.
+pub type Scanner = Vec<Point>;
+
+/// One of the 24 ways a scanner can be facing: which axis each coordinate is read from,
+/// and whether it is flipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotation {
+    axes: [usize; 3],
+    signs: [i32; 3],
+}
+
+impl Rotation {
+    pub fn apply(&self, p: Point) -> Point {
+        [0, 1, 2].map(|i| self.signs[i] * p[self.axes[i]])
+    }
+}
+
+/// The 24 proper rotations: every axis permutation and sign choice that keeps the
+/// coordinate system right-handed.
+pub fn rotations() -> impl Iterator<Item = Rotation> {
+    const PERMUTATIONS: [([usize; 3], i32); 6] = [
+        ([0, 1, 2], 1),
+        ([1, 2, 0], 1),
+        ([2, 0, 1], 1),
+        ([0, 2, 1], -1),
+        ([2, 1, 0], -1),
+        ([1, 0, 2], -1),
+    ];
+    PERMUTATIONS.iter().flat_map(|&(axes, parity)| {
+        (0..8).filter_map(move |bits: i32| {
+            let signs = [0, 1, 2].map(|i| if bits >> i & 1 == 1 { -1 } else { 1 });
+            // an odd permutation needs an odd number of flips to stay a rotation
+            (signs.iter().product::<i32>() == parity).then_some(Rotation { axes, signs })
+        })
+    })
+}
+
+fn sub(a: Point, b: Point) -> Point {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
 }
 
-pub fn p2(input: &str) -> usize {
-    0
+fn add(a: Point, b: Point) -> Point {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn manhattan(a: Point, b: Point) -> i32 {
+    sub(a, b).iter().map(|c| c.abs()).sum()
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Scanner>> {
+    input
+        .trim()
+        .split("\n\n")
+        .enumerate()
+        .map(|(n, report)| {
+            let mut lines = report.lines();
+            let header = lines.next().unwrap_or_default();
+            if header != format!("--- scanner {} ---", n) {
+                return Err(Error::at(
+                    DAY,
+                    input,
+                    header,
+                    format!("expected the header of scanner {}, found `{}`", n, header),
+                ));
+            }
+            lines
+                .map(|line| {
+                    let mut coords = line.split(',');
+                    let mut coord = || {
+                        let c = coords
+                            .next()
+                            .ok_or_else(|| Error::at(DAY, input, line, "expected 3 coordinates"))?;
+                        parse_number(DAY, input, c)
+                    };
+                    let point = [coord()?, coord()?, coord()?];
+                    match coords.next() {
+                        Some(extra) => Err(Error::at(
+                            DAY,
+                            input,
+                            extra,
+                            format!("expected 3 coordinates, found more: `{}`", extra),
+                        )),
+                        None => Ok(point),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Finds the rotation and position that make `scanner` see at least [`OVERLAP`] of `known`.
+///
+/// Every pair of a known beacon and a rotated reported beacon votes for the scanner
+/// position that would make them the same beacon.
+fn align(known: &[Point], scanner: &[Point]) -> Option<(Rotation, Point)> {
+    rotations().find_map(|rotation| {
+        let mut votes = HashMap::new();
+        for &b in scanner {
+            let b = rotation.apply(b);
+            for &k in known {
+                let count = votes.entry(sub(k, b)).or_insert(0);
+                *count += 1;
+                if *count >= OVERLAP {
+                    return Some((rotation, sub(k, b)));
+                }
+            }
+        }
+        None
+    })
+}
+
+/// The beacons and scanners, all relative to scanner 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Map {
+    pub beacons: HashSet<Point>,
+    pub scanners: Vec<Point>,
+}
+
+/// Aligns every scanner with scanner 0, going through scanners already aligned.
+pub fn assemble(scanners: &[Scanner]) -> Result<Map> {
+    let first = scanners
+        .first()
+        .ok_or_else(|| Error::new(DAY, "there are no scanners"))?;
+
+    let mut positions: Vec<Option<Point>> = vec![None; scanners.len()];
+    positions[0] = Some([0, 0, 0]);
+    let mut aligned = vec![first.clone()];
+    let mut frontier = vec![0];
+
+    while let Some(reference) = frontier.pop() {
+        for (i, scanner) in scanners.iter().enumerate() {
+            if positions[i].is_some() {
+                continue;
+            }
+            if let Some((rotation, position)) = align(&aligned[reference], scanner) {
+                positions[i] = Some(position);
+                aligned.push(
+                    scanner
+                        .iter()
+                        .map(|&b| add(rotation.apply(b), position))
+                        .collect(),
+                );
+                // aligned beacons are indexed by the order scanners were found in
+                frontier.push(aligned.len() - 1);
+            }
+        }
+    }
+
+    let scanners = positions
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            p.ok_or_else(|| {
+                Error::new(
+                    DAY,
+                    format!(
+                        "scanner {} sees fewer than {} beacons in common with the others",
+                        i, OVERLAP
+                    ),
+                )
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(Map {
+        beacons: aligned.into_iter().flatten().collect(),
+        scanners,
+    })
+}
+
+fn largest_distance(map: &Map) -> i32 {
+    map.scanners
+        .iter()
+        .flat_map(|&a| map.scanners.iter().map(move |&b| manhattan(a, b)))
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn p1(input: &str) -> Result<usize> {
+    Ok(assemble(&parse_input(input)?)?.beacons.len())
+}
+
+pub fn p2(input: &str) -> Result<i32> {
+    Ok(largest_distance(&assemble(&parse_input(input)?)?))
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = DAY;
+    type Input = Vec<Scanner>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(scanners: &Self::Input) -> Result<Answer> {
+        Ok(assemble(scanners)?.beacons.len().into())
+    }
+
+    fn part2(scanners: &Self::Input) -> Result<Answer> {
+        Ok(largest_distance(&assemble(scanners)?).into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EXAMPLE_INPUT: &str = r#""#;
-    const PROBLEM_INPUT: &str = include_str!("inputs/d11.txt");
+    const EXAMPLE_INPUT: &str = include_str!("inputs/d19_example.txt");
+
+    const EXAMPLE_P1_ANSWER: usize = 79;
+    const EXAMPLE_P2_ANSWER: i32 = 3621;
 
-    const EXAMPLE_P1_ANSWER: usize = 0;
-    const EXAMPLE_P2_ANSWER: usize = 0;
-    const PROBLEM_P1_ANSWER: usize = 0;
-    const PROBLEM_P2_ANSWER: usize = 0;
+    #[test]
+    fn test_rotations() {
+        let turned: HashSet<Point> = rotations().map(|r| r.apply([1, 2, 3])).collect();
+        assert_eq!(turned.len(), 24);
+        // no mirror images: the orientation of the x, y and z axes is preserved
+        assert!(!turned.contains(&[-1, 2, 3]));
+    }
 
     #[test]
     fn test_example_p1() {
-        assert_eq!(p1(EXAMPLE_INPUT), EXAMPLE_P1_ANSWER);
+        assert_eq!(p1(EXAMPLE_INPUT), Ok(EXAMPLE_P1_ANSWER));
     }
 
     #[test]
     fn test_example_p2() {
-        assert_eq!(p2(EXAMPLE_INPUT), EXAMPLE_P2_ANSWER);
+        assert_eq!(p2(EXAMPLE_INPUT), Ok(EXAMPLE_P2_ANSWER));
     }
 
     #[test]
-    fn test_problem_p1() {
-        assert_eq!(p1(PROBLEM_INPUT), PROBLEM_P1_ANSWER);
+    fn test_assemble() {
+        let map = assemble(&parse_input(EXAMPLE_INPUT).unwrap()).unwrap();
+        assert_eq!(
+            map.scanners,
+            [
+                [0, 0, 0],
+                [68, -1246, -43],
+                [1105, -1205, 1229],
+                [-92, -2380, -20],
+                [-20, -1133, 1061]
+            ]
+        );
+        assert!(map.beacons.contains(&[459, -707, 401]));
+        assert!(map.beacons.contains(&[-739, -1745, 668]));
     }
 
     #[test]
-    fn test_problem_p2() {
-        assert_eq!(p2(PROBLEM_INPUT), PROBLEM_P2_ANSWER);
+    fn test_bad_input() {
+        let err = p1("--- scanner 0 ---\n1,2,3\n\n--- scanner 2 ---\n1,2,3\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 19, line 4, column 1: expected the header of scanner 1, found `--- scanner 2 ---`"
+        );
+
+        let err = p1("--- scanner 0 ---\n1,2,x\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 19, line 2, column 5: expected a number, found `x`"
+        );
+
+        let err = p1("--- scanner 0 ---\n1,2\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 19, line 2, column 1: expected 3 coordinates"
+        );
+
+        let err = p1("--- scanner 0 ---\n1,2,3\n4,5,6,7\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 19, line 3, column 7: expected 3 coordinates, found more: `7`"
+        );
+
+        let err = p1("--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n1,2,3\n").unwrap_err();
+        assert_eq!(
+            err.reason,
+            "scanner 1 sees fewer than 12 beacons in common with the others"
+        );
     }
 }
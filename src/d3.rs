@@ -1,16 +1,81 @@
 use std::collections::HashSet;
 
+use crate::error::{Error, Result};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 3;
+
 type Bit = bool;
 type Word = Vec<Bit>;
-type Words = HashSet<Word>;
+pub type Words = HashSet<Word>;
 type Number = u32;
 
-pub fn day3_p1(data: &str) -> u32 {
-    let words: Words = data
-        .lines()
-        .map(|line| line.chars().map(|c| c == '1').collect())
-        .collect();
-    let width = words.iter().next().unwrap().len();
+/// The widest report whose numbers, and the mask of all their bits, fit a [`Number`].
+const MAX_WIDTH: usize = 31;
+
+fn parse_word(data: &str, line: &str) -> Result<Word> {
+    line.char_indices()
+        .map(|(i, c)| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(Error::at(
+                DAY,
+                data,
+                &line[i..],
+                format!("expected a bit, found `{}`", c),
+            )),
+        })
+        .collect()
+}
+
+pub fn parse_input(data: &str) -> Result<Words> {
+    let width = data.lines().next().map_or(0, str::len);
+    if width == 0 {
+        return Err(Error::new(DAY, "the report is empty"));
+    }
+    if width > MAX_WIDTH {
+        return Err(Error::at(
+            DAY,
+            data,
+            data.lines().next().unwrap(),
+            format!("numbers of {} bits are wider than {}", width, MAX_WIDTH),
+        ));
+    }
+
+    data.lines()
+        .map(|line| match line.len() {
+            len if len == width => parse_word(data, line),
+            len => Err(Error::at(
+                DAY,
+                data,
+                line,
+                format!("expected {} bits, found {}", width, len),
+            )),
+        })
+        .collect()
+}
+
+pub fn day3_p1(data: &str) -> Result<u32> {
+    power_consumption(&parse_input(data)?)
+}
+
+pub fn day3_p2(data: &str) -> Result<u32> {
+    life_support_rating(&parse_input(data)?)
+}
+
+/// Number of bits per word; [`parse_input`] guarantees there is at least one word.
+fn width(words: &Words) -> usize {
+    words.iter().next().map_or(0, Vec::len)
+}
+
+/// `a * b`, or an error naming the rating that does not fit a [`Number`].
+fn product(a: Number, b: Number, what: &str) -> Result<Number> {
+    a.checked_mul(b)
+        .ok_or_else(|| Error::new(DAY, format!("the {} does not fit in 32 bits", what)))
+}
+
+fn power_consumption(words: &Words) -> Result<u32> {
+    let width = width(words);
 
     let gamma = (0..width).fold(0, |gamma, position| {
         let ones = words.iter().filter(|word| word[position]).count();
@@ -23,23 +88,18 @@ pub fn day3_p1(data: &str) -> u32 {
     let mask = (1 << width) - 1;
     let epsilon = mask - gamma;
 
-    gamma * epsilon
+    product(gamma, epsilon, "power consumption")
 }
 
-pub fn day3_p2(data: &str) -> u32 {
-    let words: Words = data
-        .lines()
-        .map(|line| line.chars().map(|c| c == '1').collect())
-        .collect();
+fn life_support_rating(words: &Words) -> Result<u32> {
+    let oxygen = shrink(words.clone(), true)?;
+    let co2 = shrink(words.clone(), false)?;
 
-    let oxygen = shrink(words.clone(), true);
-    let co2 = shrink(words, false);
-
-    oxygen * co2
+    product(oxygen, co2, "life support rating")
 }
 
-fn shrink(mut words: Words, retain_majority: bool) -> Number {
-    let width = words.iter().next().unwrap().len();
+fn shrink(mut words: Words, retain_majority: bool) -> Result<Number> {
+    let width = width(&words);
 
     for position in 0..width {
         let ones = words.iter().filter(|word| word[position]).count();
@@ -49,14 +109,35 @@ fn shrink(mut words: Words, retain_majority: bool) -> Number {
 
         words.retain(|word| word[position] == retain_value);
 
-        if words.len() == 1 {
-            let word = words.iter().next().unwrap();
+        if let (1, Some(word)) = (words.len(), words.iter().next()) {
             // Convert bits to number.
-            return word.iter().fold(0, |x, bit| (x << 1) | *bit as Number);
+            return Ok(word.iter().fold(0, |x, bit| (x << 1) | *bit as Number));
         }
     }
 
-    panic!();
+    Err(Error::new(
+        DAY,
+        "bit criteria did not narrow the report to one number",
+    ))
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = DAY;
+    type Input = Words;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(words: &Self::Input) -> Result<Answer> {
+        Ok(power_consumption(words)?.into())
+    }
+
+    fn part2(words: &Self::Input) -> Result<Answer> {
+        Ok(life_support_rating(words)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -78,24 +159,56 @@ mod tests {
 
     #[test]
     fn test_d3_p1_example() {
-        assert_eq!(day3_p1(EXAMPLE_DATA), 198)
+        assert_eq!(day3_p1(EXAMPLE_DATA), Ok(198))
     }
 
     #[test]
     fn test_d3_p2_example() {
-        assert_eq!(day3_p2(EXAMPLE_DATA), 230)
+        assert_eq!(day3_p2(EXAMPLE_DATA), Ok(230))
     }
 
     // tests here
     #[test]
     fn test_d3_p1() {
         let input = include_str!("inputs/d3.txt");
-        assert_eq!(day3_p1(input), 2498354)
+        assert_eq!(day3_p1(input), Ok(2498354))
     }
 
     #[test]
     fn test_d3_p2() {
         let input = include_str!("inputs/d3.txt");
-        assert_eq!(day3_p2(input), 3277956)
+        assert_eq!(day3_p2(input), Ok(3277956))
+    }
+
+    #[test]
+    fn test_d3_bad_input() {
+        let err = day3_p1("00100\n11120\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 3, line 2, column 4: expected a bit, found `2`"
+        );
+
+        let err = day3_p1("00100\n1111\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 3, line 2, column 1: expected 5 bits, found 4"
+        );
+
+        let wide = format!("{}\n{}\n", "1".repeat(32), "0".repeat(32));
+        let err = day3_p1(&wide).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 3, line 1, column 1: numbers of 32 bits are wider than 31"
+        );
+
+        let high = format!("{}{}", "1".repeat(16), "0".repeat(15));
+        let low = format!("{}{}", "0".repeat(16), "1".repeat(15));
+        let err = day3_p1(&high).unwrap_err();
+        assert_eq!(err.reason, "the power consumption does not fit in 32 bits");
+        let err = day3_p2(&format!("{}\n{}\n", high, low)).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "the life support rating does not fit in 32 bits"
+        );
     }
 }
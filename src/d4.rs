@@ -1,5 +1,11 @@
 use std::collections::HashSet;
 
+use crate::error::{parse_number, Error, Result};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 4;
+const CARD_SIZE: usize = 25;
+
 const WINNING_PATTERNS: [(usize, usize, usize, usize, usize); 10] = [
     // Rows
     (0, 1, 2, 3, 4),
@@ -26,44 +32,64 @@ impl BingoCard {
         self.nums
             .iter()
             .enumerate()
-            .filter(|(i, _)| None == self.marked_nums_idx.get(i))
+            .filter(|(i, _)| !self.marked_nums_idx.contains(i))
             .map(|(_, num)| *num)
             .collect()
     }
 }
 
-pub fn parse_input(input: &str) -> (Vec<usize>, Vec<BingoCard>) {
+pub type Game = (Vec<usize>, Vec<BingoCard>);
+
+pub fn parse_input(input: &str) -> Result<Game> {
     // Parse first line
     let mut parts = input.trim().split("\n\n");
 
     let numbers: Vec<usize> = parts
         .next()
-        .unwrap()
+        .unwrap_or_default()
         .split(',')
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect();
+        .map(|s| parse_number(DAY, input, s))
+        .collect::<Result<_>>()?;
 
     let cards = parts
         .map(|card| {
             let non_marked_nums = card
                 .split_ascii_whitespace()
-                .map(&str::parse::<usize>)
-                .map(Result::unwrap)
-                .collect::<Vec<_>>();
+                .map(|s| parse_number(DAY, input, s))
+                .collect::<Result<Vec<_>>>()?;
+
+            if non_marked_nums.len() != CARD_SIZE {
+                return Err(Error::at(
+                    DAY,
+                    input,
+                    card,
+                    format!(
+                        "expected {} numbers on the card, found {}",
+                        CARD_SIZE,
+                        non_marked_nums.len()
+                    ),
+                ));
+            }
 
-            BingoCard {
+            Ok(BingoCard {
                 nums: non_marked_nums,
                 marked_nums_idx: HashSet::new(),
-            }
+            })
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>>>()?;
 
-    (numbers, cards)
+    Ok((numbers, cards))
 }
 
-pub fn day4_p1(input: &str) -> usize {
-    let (numbers, mut cards) = parse_input(input);
+pub fn day4_p1(input: &str) -> Result<usize> {
+    first_winning_score(parse_input(input)?)
+}
+
+pub fn day4_p2(input: &str) -> Result<usize> {
+    last_winning_score(parse_input(input)?)
+}
 
+fn first_winning_score((numbers, mut cards): Game) -> Result<usize> {
     // play the game
     for number in numbers {
         for card in &mut cards {
@@ -82,17 +108,16 @@ pub fn day4_p1(input: &str) -> usize {
                     card.marked_nums_idx.get(&pat.4),
                 ) {
                     let non_marked_sum: usize = card.non_marked_nums().iter().sum();
-                    return number * non_marked_sum;
+                    return Ok(number * non_marked_sum);
                 }
             }
             // validate if you win
         }
     }
-    0
+    Err(Error::new(DAY, "no card ever wins"))
 }
 
-pub fn day4_p2(input: &str) -> usize {
-    let (numbers, mut cards) = parse_input(input);
+fn last_winning_score((numbers, mut cards): Game) -> Result<usize> {
     let mut cards_that_won: HashSet<usize> = HashSet::new();
     let mut winnings: Vec<usize> = Vec::new();
 
@@ -106,7 +131,7 @@ pub fn day4_p2(input: &str) -> usize {
             }
 
             // this card won a long time ago skip;
-            if cards_that_won.get(&card_idx).is_some() {
+            if cards_that_won.contains(&card_idx) {
                 continue;
             }
 
@@ -127,7 +152,29 @@ pub fn day4_p2(input: &str) -> usize {
         }
     }
 
-    *winnings.last().unwrap()
+    winnings
+        .last()
+        .copied()
+        .ok_or_else(|| Error::new(DAY, "no card ever wins"))
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = DAY;
+    type Input = Game;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(game: &Self::Input) -> Result<Answer> {
+        Ok(first_winning_score(game.clone())?.into())
+    }
+
+    fn part2(game: &Self::Input) -> Result<Answer> {
+        Ok(last_winning_score(game.clone())?.into())
+    }
 }
 
 #[cfg(test)]
@@ -137,24 +184,24 @@ mod tests {
     #[test]
     fn test_day4_p1_example() {
         let input = include_str!("inputs/d4_example.txt");
-        assert_eq!(day4_p1(input), 4512); // 188 * 24 = 4512
+        assert_eq!(day4_p1(input), Ok(4512)); // 188 * 24 = 4512
     }
 
     #[test]
     fn test_day4_p1() {
         let input = include_str!("inputs/d4.txt");
-        assert_eq!(day4_p1(input), 72770); // 188 * 24 = 4512
+        assert_eq!(day4_p1(input), Ok(72770)); // 188 * 24 = 4512
     }
 
     #[test]
     fn test_day4_p2_example() {
         let input = include_str!("inputs/d4_example.txt");
-        assert_eq!(day4_p2(input), 1924); // 188 * 24 = 4512
+        assert_eq!(day4_p2(input), Ok(1924)); // 188 * 24 = 4512
     }
 
     #[test]
     fn test_day4_p2() {
         let input = include_str!("inputs/d4.txt");
-        assert_eq!(day4_p2(input), 13912); // 188 * 24 = 4512
+        assert_eq!(day4_p2(input), Ok(13912)); // 188 * 24 = 4512
     }
 }
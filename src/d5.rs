@@ -1,25 +1,37 @@
 use std::collections::HashMap;
 
+use crate::error::{parse_number, split_once, Error, Result};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 5;
+
 pub type Point = (i32, i32); // x,y
 pub type Line = (Point, Point); // start,end
 
-pub fn parse_point(input_point: &str) -> Point {
-    let coords = input_point
-        .trim()
-        .split(',')
-        .map(|input_coord| input_coord.parse::<i32>().unwrap())
-        .collect::<Vec<i32>>();
+pub fn parse_point(input: &str, input_point: &str) -> Result<Point> {
+    let (x, y) = split_once(DAY, input, input_point.trim(), ",")?;
 
-    (coords[0], coords[1])
+    Ok((parse_number(DAY, input, x)?, parse_number(DAY, input, y)?))
 }
-pub fn parse_line(input_line: &str) -> Line {
-    let points = input_line.split("->").map(parse_point).collect::<Vec<_>>();
 
-    (points[0], points[1])
+pub fn parse_line(input: &str, input_line: &str) -> Result<Line> {
+    let (start, end) = split_once(DAY, input, input_line, "->")?;
+    let ((x1, y1), (x2, y2)) = (parse_point(input, start)?, parse_point(input, end)?);
+
+    if x1 != x2 && y1 != y2 && (x2 - x1).abs() != (y2 - y1).abs() {
+        return Err(Error::at(
+            DAY,
+            input,
+            input_line,
+            "lines must be horizontal, vertical or at 45 degrees",
+        ));
+    }
+
+    Ok(((x1, y1), (x2, y2)))
 }
 
-pub fn parse_input(input: &str) -> Vec<Line> {
-    input.lines().map(parse_line).collect()
+pub fn parse_input(input: &str) -> Result<Vec<Line>> {
+    input.lines().map(|line| parse_line(input, line)).collect()
 }
 
 pub fn expand_line(line: &Line, diag: bool) -> Vec<Point> {
@@ -36,7 +48,6 @@ pub fn expand_line(line: &Line, diag: bool) -> Vec<Point> {
         let intercept = y2 - (x2 * slope);
 
         let points = (x1.min(x2)..=x1.max(x2))
-            .into_iter()
             .map(|x| (x, slope * x + intercept))
             .collect();
         return points;
@@ -51,11 +62,14 @@ pub fn expand_line(line: &Line, diag: bool) -> Vec<Point> {
     v
 }
 
-pub fn d5(input: &str, diagonals: bool) -> i32 {
-    parse_input(input)
+pub fn d5(input: &str, diagonals: bool) -> Result<i32> {
+    Ok(count_overlaps(&parse_input(input)?, diagonals))
+}
+
+fn count_overlaps(lines: &[Line], diagonals: bool) -> i32 {
+    lines
         .iter()
-        .map(|l| expand_line(l, diagonals))
-        .flatten()
+        .flat_map(|l| expand_line(l, diagonals))
         .fold(HashMap::new(), |mut points, coord| {
             let p = points.entry(coord).or_insert(0);
             *p += 1;
@@ -70,6 +84,25 @@ pub fn d5(input: &str, diagonals: bool) -> i32 {
         })
 }
 
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = DAY;
+    type Input = Vec<Line>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(lines: &Self::Input) -> Result<Answer> {
+        Ok(count_overlaps(lines, false).into())
+    }
+
+    fn part2(lines: &Self::Input) -> Result<Answer> {
+        Ok(count_overlaps(lines, true).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,24 +110,24 @@ mod tests {
     #[test]
     fn test_day5_p1_example() {
         let input = include_str!("inputs/d5_example.txt");
-        assert_eq!(d5(input, false), 5);
+        assert_eq!(d5(input, false), Ok(5));
     }
 
     #[test]
     fn test_day5_p1() {
         let input = include_str!("inputs/d5.txt");
-        assert_eq!(d5(input, false), 5585); // 188 * 24 = 4512
+        assert_eq!(d5(input, false), Ok(5585)); // 188 * 24 = 4512
     }
 
     #[test]
     fn test_day5_p2_example() {
         let input = include_str!("inputs/d5_example.txt");
-        assert_eq!(d5(input, true), 12);
+        assert_eq!(d5(input, true), Ok(12));
     }
 
     #[test]
     fn test_day5_p2() {
         let input = include_str!("inputs/d5.txt");
-        assert_eq!(d5(input, true), 17193); // 188 * 24 = 4512
+        assert_eq!(d5(input, true), Ok(17193)); // 188 * 24 = 4512
     }
 }
@@ -2,15 +2,32 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 
-pub fn parse_input(input: &str) -> Vec<usize> {
+use crate::error::{parse_number, Error, Result};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 6;
+
+pub fn parse_input(input: &str) -> Result<Vec<usize>> {
     input
         .split(',')
-        .map(|c| c.parse::<usize>().unwrap())
+        .map(|c| match parse_number(DAY, input, c)? {
+            timer if timer <= 8 => Ok(timer),
+            timer => Err(Error::at(
+                DAY,
+                input,
+                c.trim(),
+                format!("timers go from 0 to 8, found {}", timer),
+            )),
+        })
         .collect()
 }
 
-pub fn world(input: &str, ticks: usize) -> usize {
-    let mut world = parse_input(input).into_iter().counts();
+pub fn world(input: &str, ticks: usize) -> Result<usize> {
+    Ok(simulate(&parse_input(input)?, ticks))
+}
+
+fn simulate(fish: &[usize], ticks: usize) -> usize {
+    let mut world = fish.iter().copied().counts();
 
     for _tick in 0..ticks {
         let mut next_world: HashMap<usize, usize> = HashMap::new();
@@ -36,14 +53,33 @@ pub fn world(input: &str, ticks: usize) -> usize {
     world.iter().map(|(_, &v)| v).sum()
 }
 
-pub fn p1(input: &str) -> usize {
+pub fn p1(input: &str) -> Result<usize> {
     world(input, 80)
 }
 
-pub fn p2(input: &str) -> usize {
+pub fn p2(input: &str) -> Result<usize> {
     world(input, 256)
 }
 
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = DAY;
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(fish: &Self::Input) -> Result<Answer> {
+        Ok(simulate(fish, 80).into())
+    }
+
+    fn part2(fish: &Self::Input) -> Result<Answer> {
+        Ok(simulate(fish, 256).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,24 +87,33 @@ mod tests {
     #[test]
     fn test_p1_example() {
         let input = include_str!("inputs/d6_example.txt");
-        assert_eq!(p1(input), 5934);
+        assert_eq!(p1(input), Ok(5934));
     }
 
     #[test]
     fn test_p1() {
         let input = include_str!("inputs/d6.txt");
-        assert_eq!(p1(input), 386755);
+        assert_eq!(p1(input), Ok(386755));
     }
 
     #[test]
     fn test_p2_example() {
         let input = include_str!("inputs/d6_example.txt");
-        assert_eq!(p2(input), 26984457539);
+        assert_eq!(p2(input), Ok(26984457539));
     }
 
     #[test]
     fn test_p2() {
         let input = include_str!("inputs/d6.txt");
-        assert_eq!(p2(input), 1732731810807);
+        assert_eq!(p2(input), Ok(1732731810807));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("3,4,3,1,9").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 6, line 1, column 9: timers go from 0 to 8, found 9"
+        );
     }
 }
@@ -1,13 +1,27 @@
-pub fn parse_input(input: &str) -> Vec<i32> {
+use crate::error::{parse_number, Result};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 7;
+
+/// Parses the crab positions. There is always at least one, since `split` yields at least once.
+pub fn parse_input(input: &str) -> Result<Vec<i32>> {
     input
         .trim()
         .split(',')
-        .map(|c| c.parse::<i32>().unwrap())
+        .map(|c| parse_number(DAY, input, c))
         .collect()
 }
 
-pub fn p1(input: &str) -> i32 {
-    let mut crab_positions = parse_input(input);
+pub fn p1(input: &str) -> Result<i32> {
+    Ok(linear_fuel(&parse_input(input)?))
+}
+
+pub fn p2(input: &str) -> Result<i32> {
+    Ok(triangular_fuel(&parse_input(input)?))
+}
+
+fn linear_fuel(crab_positions: &[i32]) -> i32 {
+    let mut crab_positions = crab_positions.to_vec();
     crab_positions.sort_unstable();
 
     let median = crab_positions[crab_positions.len() / 2];
@@ -15,8 +29,7 @@ pub fn p1(input: &str) -> i32 {
     crab_positions.iter().map(|pos| (pos - median).abs()).sum()
 }
 
-pub fn p2(input: &str) -> i32 {
-    let crab_positions = parse_input(input);
+fn triangular_fuel(crab_positions: &[i32]) -> i32 {
     let mean_f: f32 = crab_positions.iter().sum::<i32>() as f32 / crab_positions.len() as f32;
 
     let ceil = mean_f.ceil() as i32;
@@ -32,6 +45,25 @@ pub fn p2(input: &str) -> i32 {
     ceil_sum
 }
 
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = DAY;
+    type Input = Vec<i32>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(crab_positions: &Self::Input) -> Result<Answer> {
+        Ok(linear_fuel(crab_positions).into())
+    }
+
+    fn part2(crab_positions: &Self::Input) -> Result<Answer> {
+        Ok(triangular_fuel(crab_positions).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,25 +73,33 @@ mod tests {
     #[test]
     fn test_p1_example() {
         let input = EXAMPLE;
-        assert_eq!(p1(input), 37);
+        assert_eq!(p1(input), Ok(37));
     }
 
     #[test]
     fn test_p1() {
         let input = include_str!("inputs/d7.txt");
-        assert_eq!(p1(input), 355521);
+        assert_eq!(p1(input), Ok(355521));
     }
 
     #[test]
     fn test_p2_example() {
         let input = EXAMPLE;
-        assert_eq!(p2(input), 168);
+        assert_eq!(p2(input), Ok(168));
     }
 
     #[test]
     fn test_p2() {
         let input = include_str!("inputs/d7.txt");
-        p2(EXAMPLE);
-        assert_eq!(p2(input), 100148861);
+        assert_eq!(p2(input), Ok(100148861));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("16,1,,0").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 7, line 1, column 6: expected a number, found ``"
+        );
     }
 }
@@ -1,11 +1,103 @@
 use std::collections::HashSet;
 
-pub fn p1(input: &str) -> usize {
+use crate::error::{split_once, Error, Result};
+use crate::solution::{Answer, Solution};
+
+const DAY: u8 = 8;
+
+type Pattern = HashSet<char>;
+
+/// One line of notes: the ten unique signal patterns and the four output digits.
+pub struct Entry {
+    patterns: Vec<Pattern>,
+    output: Vec<Pattern>,
+}
+
+fn parse_pattern(input: &str, s: &str) -> Result<Pattern> {
+    if !(2..=7).contains(&s.chars().count()) {
+        return Err(Error::at(
+            DAY,
+            input,
+            s,
+            format!("`{}` does not light between 2 and 7 segments", s),
+        ));
+    }
+    let mut pattern = Pattern::new();
+    for (i, c) in s.char_indices() {
+        let reason = if !('a'..='g').contains(&c) {
+            format!("unknown segment `{}`", c)
+        } else if !pattern.insert(c) {
+            format!("segment `{}` appears twice in `{}`", c, s)
+        } else {
+            continue;
+        };
+        return Err(Error::at(DAY, input, &s[i..], reason));
+    }
+    Ok(pattern)
+}
+
+fn parse_patterns(input: &str, side: &str, count: usize) -> Result<Vec<Pattern>> {
+    let patterns = side
+        .split(' ')
+        .map(|s| parse_pattern(input, s))
+        .collect::<Result<Vec<_>>>()?;
+
+    if patterns.len() != count {
+        return Err(Error::at(
+            DAY,
+            input,
+            side,
+            format!("expected {} patterns, found {}", count, patterns.len()),
+        ));
+    }
+    Ok(patterns)
+}
+
+/// Fails if two of the ten unique patterns light the same segments.
+fn check_distinct(input: &str, side: &str, patterns: &[Pattern]) -> Result<()> {
+    for (i, (pattern, s)) in patterns.iter().zip(side.split(' ')).enumerate() {
+        if patterns[..i].contains(pattern) {
+            return Err(Error::at(
+                DAY,
+                input,
+                s,
+                format!("`{}` lights the same segments as an earlier pattern", s),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Entry>> {
     input
         .trim()
         .lines()
-        .flat_map(|line| line.split(" | ").nth(1).unwrap().split(' '))
-        .filter(|&v| matches!(v.len(), 2 | 3 | 4 | 7))
+        .map(|line| {
+            let (patterns, output) = split_once(DAY, input, line, " | ")?;
+
+            let unique = parse_patterns(input, patterns, 10)?;
+            check_distinct(input, patterns, &unique)?;
+            Ok(Entry {
+                patterns: unique,
+                output: parse_patterns(input, output, 4)?,
+            })
+        })
+        .collect()
+}
+
+pub fn p1(input: &str) -> Result<usize> {
+    Ok(count_easy_digits(&parse_input(input)?))
+}
+
+pub fn p2(input: &str) -> Result<i32> {
+    sum_outputs(&parse_input(input)?)
+}
+
+fn count_easy_digits(entries: &[Entry]) -> usize {
+    entries
+        .iter()
+        .flat_map(|entry| &entry.output)
+        .filter(|v| matches!(v.len(), 2 | 3 | 4 | 7))
         .count()
 }
 
@@ -20,19 +112,14 @@ pub fn p1(input: &str) -> usize {
 // 5 ab.d.fg => len = 5 & 4.diff(5) == 1
 // 2 a.cde.g => len = 5 & 4.diff(2) == 2
 
-pub fn p2(input: &str) -> i32 {
+fn sum_outputs(entries: &[Entry]) -> Result<i32> {
     let mut total = 0;
 
-    for line in input.trim().lines() {
-        let mut sides = line.split(" | ").map(|side| {
-            side.split(' ')
-                .map(|s| s.chars().collect::<HashSet<char>>())
-        });
-
-        let possible_patterns = sides.next().unwrap();
-        let display_side = sides.next().unwrap();
+    for (n, entry) in entries.iter().enumerate() {
+        let possible_patterns = entry.patterns.iter().cloned();
+        let mut display_side = entry.output.iter();
 
-        let mut mapping: [HashSet<char>; 10] = Default::default();
+        let mut mapping: [Pattern; 10] = Default::default();
         let mut patterns_round_2: Vec<_> = Default::default();
 
         // Decoding level 1 - Easy to decode patterns 1,4,7,8
@@ -56,18 +143,48 @@ pub fn p2(input: &str) -> i32 {
                 (6, i) if mapping[4].difference(&i).count() == 0 => mapping[9] = i,
                 (6, i) if mapping[1].difference(&i).count() == 0 => mapping[0] = i,
                 (6, i) => mapping[6] = i,
-                _ => unreachable!(),
+                (len, _) => {
+                    return Err(Error::new(
+                        DAY,
+                        format!("entry {} has a pattern of {} segments", n + 1, len),
+                    ))
+                }
             }
         }
 
-        let number = display_side.fold(0, |number, pattern| {
-            number * 10 + mapping.iter().position(|x| pattern.eq(x)).unwrap() as i32
-        });
+        let number = display_side.try_fold(0, |number, pattern| {
+            match mapping.iter().position(|x| pattern.eq(x)) {
+                Some(digit) => Ok(number * 10 + digit as i32),
+                None => Err(Error::new(
+                    DAY,
+                    format!("entry {} shows a digit that cannot be decoded", n + 1),
+                )),
+            }
+        })?;
 
         total += number;
     }
 
-    total
+    Ok(total)
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = DAY;
+    type Input = Vec<Entry>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(entries: &Self::Input) -> Result<Answer> {
+        Ok(count_easy_digits(entries).into())
+    }
+
+    fn part2(entries: &Self::Input) -> Result<Answer> {
+        Ok(sum_outputs(entries)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -77,24 +194,67 @@ mod tests {
     #[test]
     fn test_p1_example() {
         let input = include_str!("inputs/d8_example.txt");
-        assert_eq!(p1(input), 26);
+        assert_eq!(p1(input), Ok(26));
     }
 
     #[test]
     fn test_p1() {
         let input = include_str!("inputs/d8.txt");
-        assert_eq!(p1(input), 278);
+        assert_eq!(p1(input), Ok(278));
     }
 
     #[test]
     fn test_p2_example() {
         let input = include_str!("inputs/d8_example.txt");
-        assert_eq!(p2(input), 61229);
+        assert_eq!(p2(input), Ok(61229));
     }
 
     #[test]
     fn test_p2() {
         let input = include_str!("inputs/d8.txt");
-        assert_eq!(p2(input), 986179);
+        assert_eq!(p2(input), Ok(986179));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let line =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+        assert_eq!(p2(line), Ok(5353));
+
+        let err = p1(&line.replace(" | ", " ")).unwrap_err();
+        assert_eq!(err.location.map(|l| l.column), Some(1));
+
+        let err = p1(&line.replace("cdfgeb", "cdfgeh")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 8, line 1, column 43: unknown segment `h`"
+        );
+
+        let err = p1(&line.replace("cdfgeb", "cdféeb")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 8, line 1, column 41: unknown segment `é`"
+        );
+
+        let err = p2(&line.replace("cdfgeb", "cdfgeg")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 8, line 1, column 43: segment `g` appears twice in `cdfgeg`"
+        );
+
+        let err = p2(&line.replace(" dab ", " ab ")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 8, line 1, column 56: `ab` lights the same segments as an earlier pattern"
+        );
+        // the order of the segments does not matter
+        let err = p2(&line.replace(" dab ", " ba ")).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "`ab` lights the same segments as an earlier pattern"
+        );
+
+        let err = p1(&line.replace(" fcadb", "")).unwrap_err();
+        assert_eq!(err.reason, "expected 4 patterns, found 3");
     }
 }
@@ -1,76 +1,28 @@
 use std::collections::HashSet;
 
-struct Map {
-    values: Vec<u8>,
-    width: i32,
-    height: i32,
-}
+use crate::error::Result;
+use crate::grid::{Grid, Point};
+use crate::solution::{Answer, Solution};
 
-impl From<&str> for Map {
-    fn from(input: &str) -> Self {
-        let lines = input.trim().lines().enumerate();
-
-        let (values, width, height) = lines.fold(
-            (Vec::new(), 0, 0),
-            |(mut points, _, _), (line_idx, line)| {
-                let mut new_points = line
-                    .chars()
-                    .map(|c| c.to_digit(10).unwrap() as u8)
-                    .collect::<Vec<u8>>();
-                points.append(&mut new_points);
-                (points, line.len() as i32, (line_idx + 1) as i32)
-            },
-        );
+const DAY: u8 = 9;
 
-        Map {
-            values,
-            width,
-            height,
-        }
-    }
-}
+pub type Map = Grid<u8>;
 
-impl Map {
-    fn get(&self, x: i32, y: i32) -> Option<u8> {
-        let in_bounds = x >= 0 && x < self.width && y >= 0 && y < self.height;
-        in_bounds.then(|| self.values[(x + y * self.width) as usize])
-    }
+pub fn parse_input(input: &str) -> Result<Map> {
+    Grid::parse_digits(DAY, input)
 }
 
-fn get_low_points(grid: &Map) -> HashSet<(i32, i32)> {
-    (0..grid.width)
-        .flat_map(|x| {
-            (0..grid.height).filter_map(move |y| {
-                let center = grid.get(x, y).unwrap();
-                let ids = [(x - 1, y), (x, y - 1), (x + 1, y), (x, y + 1)];
-
-                let basin = ids
-                    .iter()
-                    .filter_map(|(xp, yp)| grid.get(*xp, *yp))
-                    .all(|x| center < x);
-
-                if basin {
-                    Some((x, y))
-                } else {
-                    None
-                }
-            })
-        })
+fn get_low_points(grid: &Map) -> Vec<Point> {
+    grid.points()
+        .filter(|&p| grid.neighbours4(p).all(|n| grid[p] < grid[n]))
         .collect()
 }
 
-fn flood_recurs<'a>(
-    grid: &Map,
-    x: i32,
-    y: i32,
-    pts: &'a mut HashSet<(i32, i32)>,
-) -> &'a mut HashSet<(i32, i32)> {
-    if pts.insert((x, y)) {
-        for (xp, yp) in [(x - 1, y), (x, y - 1), (x + 1, y), (x, y + 1)] {
-            if let Some(val) = grid.get(xp, yp) {
-                if val != 9 {
-                    flood_recurs(grid, xp, yp, pts);
-                }
+fn flood_recurs<'a>(grid: &Map, p: Point, pts: &'a mut HashSet<Point>) -> &'a mut HashSet<Point> {
+    if pts.insert(p) {
+        for n in grid.neighbours4(p) {
+            if grid[n] != 9 {
+                flood_recurs(grid, n, pts);
             }
         }
     }
@@ -78,34 +30,51 @@ fn flood_recurs<'a>(
     pts
 }
 
-fn basin_size(grid: &Map, x: i32, y: i32) -> usize {
+fn basin_size(grid: &Map, p: Point) -> usize {
     let mut pts = HashSet::new();
-    flood_recurs(grid, x, y, &mut pts).len()
+    flood_recurs(grid, p, &mut pts).len()
 }
 
-pub fn p1(input: &str) -> u32 {
-    let map = Map::from(input);
+pub fn p1(input: &str) -> Result<u32> {
+    Ok(risk_level(&parse_input(input)?))
+}
 
-    let low_points = get_low_points(&map);
-    low_points
-        .iter()
-        .map(|(x, y)| map.get(*x, *y).unwrap() as u32 + 1)
-        .sum()
+pub fn p2(input: &str) -> Result<usize> {
+    Ok(largest_basins(&parse_input(input)?))
 }
 
-pub fn p2(input: &str) -> usize {
-    let map = Map::from(input);
+fn risk_level(map: &Map) -> u32 {
+    let low_points = get_low_points(map);
+    low_points.iter().map(|&p| map[p] as u32 + 1).sum()
+}
 
-    let low_points = get_low_points(&map);
-    let mut basin_sizes: Vec<usize> = low_points
-        .iter()
-        .map(|(x, y)| basin_size(&map, *x, *y))
-        .collect();
+fn largest_basins(map: &Map) -> usize {
+    let low_points = get_low_points(map);
+    let mut basin_sizes: Vec<usize> = low_points.iter().map(|&p| basin_size(map, p)).collect();
 
     basin_sizes.sort_unstable();
     basin_sizes.iter().rev().take(3).product()
 }
 
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = DAY;
+    type Input = Map;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(map: &Self::Input) -> Result<Answer> {
+        Ok(risk_level(map).into())
+    }
+
+    fn part2(map: &Self::Input) -> Result<Answer> {
+        Ok(largest_basins(map).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,24 +87,36 @@ mod tests {
 
     #[test]
     fn test_p1_example() {
-        assert_eq!(p1(EXAMPLE), 15);
+        assert_eq!(p1(EXAMPLE), Ok(15));
     }
 
     #[test]
     fn test_p1() {
         let input = include_str!("inputs/d9.txt");
-        assert_eq!(p1(input), 591);
+        assert_eq!(p1(input), Ok(591));
     }
 
     #[test]
     fn test_p2_example() {
         let input = EXAMPLE;
-        assert_eq!(p2(input), 1134);
+        assert_eq!(p2(input), Ok(1134));
     }
 
     #[test]
     fn test_p2() {
         let input = include_str!("inputs/d9.txt");
-        assert_eq!(p2(input), 1113424);
+        assert_eq!(p2(input), Ok(1113424));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = p1("2199\n39x7\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 9, line 2, column 3: expected a digit, found `x`"
+        );
+
+        let err = p1("2199\n398\n").unwrap_err();
+        assert_eq!(err.reason, "expected 4 cells, found 3");
     }
 }
@@ -0,0 +1,166 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// 1-based position of a problem in a puzzle input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Why a day could not read or parse its input or compute an answer, or why the runner
+/// could not do its own work, such as keeping the answers file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// The day the problem is with, `None` for a problem of the runner's.
+    pub day: Option<u8>,
+    /// Where in the input the problem is, when it can be pinned to a spot.
+    pub location: Option<Location>,
+    pub reason: String,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// An error that is not tied to a place in the input, e.g. an input with no solution.
+    pub fn new(day: u8, reason: impl Into<String>) -> Self {
+        Error {
+            day: Some(day),
+            location: None,
+            reason: reason.into(),
+        }
+    }
+
+    /// An error about `fragment`, which must be a slice of `input`.
+    ///
+    /// The location is recovered from where `fragment` sits in `input`, so parsers can report
+    /// errors on the `&str` pieces they already hold instead of tracking line numbers.
+    pub fn at(day: u8, input: &str, fragment: &str, reason: impl Into<String>) -> Self {
+        Error {
+            day: Some(day),
+            location: locate(input, fragment),
+            reason: reason.into(),
+        }
+    }
+
+    /// A problem of the runner's rather than of any day, e.g. with the answers file.
+    pub fn other(reason: impl Into<String>) -> Self {
+        Error {
+            day: None,
+            location: None,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {}", day)?;
+            f.write_str(if self.location.is_some() { ", " } else { ": " })?;
+        }
+        if let Some(Location { line, column }) = self.location {
+            write!(f, "line {}, column {}: ", line, column)?;
+        }
+        f.write_str(&self.reason)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Finds the line and column of `fragment` in `input`, if it is a slice of it.
+pub fn locate(input: &str, fragment: &str) -> Option<Location> {
+    let start = input.as_ptr() as usize;
+    let offset = (fragment.as_ptr() as usize).checked_sub(start)?;
+    if offset + fragment.len() > input.len() {
+        return None;
+    }
+    // a `&str` lying inside `input` can only be a slice of it, which starts on a character
+    assert!(
+        input.is_char_boundary(offset),
+        "fragment starts inside a character of the input"
+    );
+
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    Some(Location { line, column })
+}
+
+/// Parses `fragment` (a slice of `input`) as a number, reporting where it failed.
+pub fn parse_number<T: FromStr>(day: u8, input: &str, fragment: &str) -> Result<T> {
+    let trimmed = fragment.trim();
+    trimmed.parse().map_err(|_| {
+        Error::at(
+            day,
+            input,
+            trimmed,
+            format!("expected a number, found `{}`", trimmed),
+        )
+    })
+}
+
+/// Splits `fragment` (a slice of `input`) around `sep`, reporting where it was expected.
+pub fn split_once<'a>(
+    day: u8,
+    input: &str,
+    fragment: &'a str,
+    sep: &str,
+) -> Result<(&'a str, &'a str)> {
+    fragment.split_once(sep).ok_or_else(|| {
+        Error::at(
+            day,
+            input,
+            fragment,
+            format!("expected `{}` in `{}`", sep, fragment),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "1,2\n3,x\n";
+
+    #[test]
+    fn test_locate() {
+        assert_eq!(
+            locate(INPUT, &INPUT[6..]),
+            Some(Location { line: 2, column: 3 })
+        );
+        assert_eq!(locate(INPUT, "3,x"), None);
+        // starts in `input`, but runs past its end
+        assert_eq!(locate(&INPUT[..5], &INPUT[3..8]), None);
+        assert_eq!(
+            locate(INPUT, &INPUT[8..]),
+            Some(Location { line: 3, column: 1 })
+        );
+    }
+
+    #[test]
+    fn test_parse_number() {
+        let line = INPUT.lines().nth(1).unwrap();
+        let (_, value) = line.split_once(',').unwrap();
+
+        let err = parse_number::<i32>(5, INPUT, value).unwrap_err();
+        assert_eq!(err.location, Some(Location { line: 2, column: 3 }));
+        assert_eq!(
+            err.to_string(),
+            "day 5, line 2, column 3: expected a number, found `x`"
+        );
+    }
+
+    #[test]
+    fn test_error_without_location() {
+        assert_eq!(
+            Error::new(4, "no card ever wins").to_string(),
+            "day 4: no card ever wins"
+        );
+        assert_eq!(
+            Error::other("cannot access answers.json").to_string(),
+            "cannot access answers.json"
+        );
+    }
+}
@@ -0,0 +1,377 @@
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+use crate::solution::Answer;
+
+use self::Input::*;
+use self::Pick::*;
+
+/// The puzzle descriptions, `dN.md`, sit at the crate root.
+pub fn path_for(day: u8) -> PathBuf {
+    PathBuf::from(format!("d{}.md", day))
+}
+
+/// An indented code block of a puzzle description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// The part whose text the block appears in, 0 before "Part One".
+    pub part: u8,
+    /// 1-based line of the first row of the block.
+    pub line: usize,
+    /// The rows of the block without their indentation, each ending with a newline.
+    pub text: String,
+}
+
+/// An inline code span of a puzzle description, like `` `37` `` or `` _`37`_ ``.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub part: u8,
+    pub line: usize,
+    pub text: String,
+    /// Set for spans in emphasis, which is how the puzzle text highlights answers.
+    pub emphasised: bool,
+}
+
+impl Span {
+    /// The value this span gives as an answer: an emphasised value, or the result of a
+    /// worked calculation such as `188 * 24 = 4512`.
+    pub fn answer(&self) -> Option<Answer> {
+        let value = match self.text.rsplit_once("= ") {
+            Some((_, result)) => result,
+            None if self.emphasised => &self.text,
+            None => return None,
+        };
+        Some(to_answer(value))
+    }
+}
+
+fn to_answer(value: &str) -> Answer {
+    match (value.parse::<i64>(), value.parse::<u64>()) {
+        (Ok(n), _) => Answer::Number(n),
+        (_, Ok(n)) => Answer::Unsigned(n),
+        _ => Answer::Text(value.to_string()),
+    }
+}
+
+/// The code blocks and code spans of a puzzle description, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub blocks: Vec<Block>,
+    pub spans: Vec<Span>,
+}
+
+impl Puzzle {
+    pub fn parse(markdown: &str) -> Self {
+        let mut part = 0;
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut spans = Vec::new();
+        let mut in_block = false;
+
+        for (i, line) in markdown.lines().enumerate() {
+            match line.strip_prefix("    ") {
+                Some(row) if !row.trim().is_empty() => {
+                    if !in_block {
+                        blocks.push(Block {
+                            part,
+                            line: i + 1,
+                            text: String::new(),
+                        });
+                        in_block = true;
+                    }
+                    let block = blocks.last_mut().unwrap();
+                    block.text.push_str(row);
+                    block.text.push('\n');
+                    continue;
+                }
+                _ => in_block = false,
+            }
+
+            if line.starts_with("### Part One") {
+                part = 1;
+            } else if line.starts_with("### Part Two") {
+                part = 2;
+            }
+
+            // code spans alternate with text between backticks
+            let pieces: Vec<&str> = line.split('`').collect();
+            for (j, text) in pieces.iter().enumerate().skip(1).step_by(2) {
+                if j + 1 == pieces.len() {
+                    // unmatched backtick
+                    break;
+                }
+                spans.push(Span {
+                    part,
+                    line: i + 1,
+                    text: text.to_string(),
+                    emphasised: pieces[j - 1].ends_with('_') && pieces[j + 1].starts_with('_'),
+                });
+            }
+        }
+
+        Puzzle { blocks, spans }
+    }
+
+    /// The code spans in the text of `part`.
+    pub fn spans_of(&self, part: u8) -> impl Iterator<Item = &Span> {
+        self.spans.iter().filter(move |s| s.part == part)
+    }
+
+    /// The spans of `part` that give an answer, see [`Span::answer`].
+    pub fn answers_of(&self, part: u8) -> impl Iterator<Item = &Span> {
+        self.spans_of(part).filter(|s| s.answer().is_some())
+    }
+}
+
+/// Where a worked example takes its input from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// These code blocks, separated by blank lines.
+    Blocks(&'static [usize]),
+    /// A code block whose rows ending in `|` are wrapped onto the next row.
+    Unwrapped(usize),
+    /// The first code span on the same line as the answer.
+    SpanOnLine,
+}
+
+/// Which span of a part holds the answer of a worked example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pick {
+    /// The last span giving an answer.
+    Last,
+    /// The n-th span giving an answer.
+    Nth(usize),
+    /// The n-th code span, for answers the text does not highlight.
+    Code(usize),
+}
+
+/// How to find one worked example in a puzzle description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spec {
+    pub day: u8,
+    pub part: u8,
+    pub input: Input,
+    pub answer: Pick,
+}
+
+const fn spec(day: u8, part: u8, input: Input, answer: Pick) -> Spec {
+    Spec {
+        day,
+        part,
+        input,
+        answer,
+    }
+}
+
+/// The worked examples of each puzzle description.
+///
+/// The descriptions do not mark which block is the example or which highlighted value
+/// answers it, so that much is spelled out here; the contents come from the text.
+#[rustfmt::skip]
+pub const SPECS: &[Spec] = &[
+    spec(1, 1, Blocks(&[0]), Last),
+    spec(1, 2, Blocks(&[0]), Last),
+    spec(2, 1, Blocks(&[0]), Last),
+    spec(2, 2, Blocks(&[0]), Last),
+    spec(3, 1, Blocks(&[0]), Last),
+    spec(3, 2, Blocks(&[0]), Last),
+    spec(4, 1, Blocks(&[0, 1, 2, 3]), Last),
+    spec(4, 2, Blocks(&[0, 1, 2, 3]), Last),
+    spec(5, 1, Blocks(&[0]), Last),
+    spec(5, 2, Blocks(&[0]), Last),
+    spec(6, 1, Blocks(&[0]), Last),
+    spec(6, 2, Blocks(&[0]), Last),
+    spec(7, 1, Blocks(&[0]), Last),
+    spec(7, 2, Blocks(&[0]), Last),
+    spec(8, 1, Unwrapped(3), Last),
+    spec(8, 2, Unwrapped(3), Last),
+    spec(9, 1, Blocks(&[0]), Last),
+    spec(9, 2, Blocks(&[0]), Last),
+    spec(10, 1, Blocks(&[1]), Last),
+    spec(10, 2, Blocks(&[1]), Last),
+    spec(11, 1, Blocks(&[0]), Last),
+    spec(11, 2, Blocks(&[0]), Last),
+    spec(12, 1, Blocks(&[0]), Last),
+    spec(12, 1, Blocks(&[3]), Code(15)),
+    spec(12, 1, Blocks(&[5]), Code(16)),
+    spec(12, 2, Blocks(&[0]), Code(4)),
+    spec(12, 2, Blocks(&[3]), Code(5)),
+    spec(12, 2, Blocks(&[5]), Code(6)),
+    spec(13, 1, Blocks(&[1, 2]), Last),
+    spec(14, 1, Blocks(&[0, 1]), Last),
+    spec(14, 2, Blocks(&[0, 1]), Last),
+    spec(15, 1, Blocks(&[0]), Last),
+    spec(15, 2, Blocks(&[0]), Last),
+    spec(16, 1, SpanOnLine, Nth(0)),
+    spec(16, 1, SpanOnLine, Nth(1)),
+    spec(16, 1, SpanOnLine, Nth(2)),
+    spec(16, 1, SpanOnLine, Nth(3)),
+    spec(16, 2, SpanOnLine, Nth(0)),
+    spec(16, 2, SpanOnLine, Nth(1)),
+    spec(16, 2, SpanOnLine, Nth(2)),
+    spec(16, 2, SpanOnLine, Nth(3)),
+    spec(17, 1, Blocks(&[0]), Last),
+    spec(17, 2, Blocks(&[0]), Last),
+];
+
+/// A worked example ready to run against a solver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub day: u8,
+    pub part: u8,
+    /// Line of the puzzle description the answer was read from.
+    pub line: usize,
+    pub input: String,
+    pub expected: Answer,
+}
+
+/// The worked examples of `day`, read from its puzzle description `markdown`.
+pub fn examples(day: u8, markdown: &str) -> Result<Vec<Example>> {
+    let puzzle = Puzzle::parse(markdown);
+    SPECS
+        .iter()
+        .filter(|spec| spec.day == day)
+        .map(|spec| extract(&puzzle, spec))
+        .collect()
+}
+
+fn extract(puzzle: &Puzzle, spec: &Spec) -> Result<Example> {
+    let missing = |what: String| {
+        Error::new(
+            spec.day,
+            format!("part {} of the puzzle text has no {}", spec.part, what),
+        )
+    };
+
+    let mut answers = puzzle.answers_of(spec.part);
+    let span = match spec.answer {
+        Last => answers.last(),
+        Nth(n) => answers.nth(n),
+        Code(n) => puzzle.spans_of(spec.part).nth(n),
+    }
+    .ok_or_else(|| missing(format!("answer {:?}", spec.answer)))?;
+    let expected = span.answer().unwrap_or_else(|| to_answer(&span.text));
+
+    let block = |i: usize| {
+        puzzle
+            .blocks
+            .get(i)
+            .map(|b| b.text.as_str())
+            .ok_or_else(|| missing(format!("code block {}", i)))
+    };
+    let input = match spec.input {
+        Blocks(indices) => indices
+            .iter()
+            .map(|&i| block(i))
+            .collect::<Result<Vec<_>>>()?
+            .join("\n"),
+        Unwrapped(i) => block(i)?.replace("|\n", "| "),
+        SpanOnLine => puzzle
+            .spans
+            .iter()
+            .find(|s| s.line == span.line)
+            .map(|s| s.text.clone())
+            .unwrap(),
+    };
+
+    Ok(Example {
+        day: spec.day,
+        part: spec.part,
+        line: span.line,
+        input,
+        expected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+    use std::fs;
+
+    const MARKDOWN: &str = "# Day 0: Test
+
+### Part One
+
+For example:
+
+    1
+    2
+
+    3
+
+The answer is _`6`_, from `1 + 2 + 3 = 6`.
+
+### Part Two
+
+Here `x` is _`-1`_ and _`y`_.
+";
+
+    #[test]
+    fn test_parse() {
+        let puzzle = Puzzle::parse(MARKDOWN);
+        assert_eq!(
+            puzzle.blocks,
+            [
+                Block {
+                    part: 1,
+                    line: 7,
+                    text: "1\n2\n".to_string()
+                },
+                Block {
+                    part: 1,
+                    line: 10,
+                    text: "3\n".to_string()
+                },
+            ]
+        );
+
+        let answers: Vec<_> = puzzle.spans.iter().map(Span::answer).collect();
+        assert_eq!(
+            answers,
+            [
+                Some(Answer::Number(6)),
+                Some(Answer::Number(6)),
+                None,
+                Some(Answer::Number(-1)),
+                Some(Answer::Text("y".to_string())),
+            ]
+        );
+        assert_eq!(puzzle.answers_of(2).count(), 2);
+    }
+
+    #[test]
+    fn test_extract() {
+        let puzzle = Puzzle::parse(MARKDOWN);
+        let example = extract(&puzzle, &spec(0, 1, Blocks(&[0, 1]), Last)).unwrap();
+        assert_eq!(example.input, "1\n2\n\n3\n");
+        assert_eq!(example.expected, Answer::Number(6));
+        assert_eq!(example.line, 12);
+
+        let example = extract(&puzzle, &spec(0, 2, SpanOnLine, Nth(0))).unwrap();
+        assert_eq!(example.input, "x");
+
+        let err = extract(&puzzle, &spec(0, 1, Blocks(&[2]), Last)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 0: part 1 of the puzzle text has no code block 2"
+        );
+    }
+
+    #[test]
+    fn test_puzzle_examples() {
+        for day in 1..=17 {
+            let markdown = fs::read_to_string(path_for(day)).unwrap();
+            let solver = solution::get(day).unwrap();
+            for example in examples(day, &markdown).unwrap() {
+                assert_eq!(
+                    solver.run(example.part, &example.input),
+                    Ok(example.expected),
+                    "day {} part {}, example answered on line {}",
+                    day,
+                    example.part,
+                    example.line
+                );
+            }
+        }
+    }
+}
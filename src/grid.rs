@@ -0,0 +1,361 @@
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+use crate::error::{Error, Result};
+
+/// An `(x, y)` position, `x` growing to the right and `y` growing down.
+pub type Point = (usize, usize);
+
+#[rustfmt::skip]
+const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+#[rustfmt::skip]
+const ALL_AROUND: [(isize, isize); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+
+/// A rectangular map of cells, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from its cells in row-major order.
+    ///
+    /// Panics if there are not exactly `width * height` cells.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height, "grid cell count mismatch");
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Point) -> T) -> Self {
+        let cells = points(width, height).map(&mut f).collect();
+        Grid::new(width, height, cells)
+    }
+
+    /// Parses one cell per character, one row per line.
+    ///
+    /// `cell` turns a character into a cell, and `what` names the cell it expected in the
+    /// error reported when it returns `None`.
+    pub fn parse(
+        day: u8,
+        input: &str,
+        what: &str,
+        cell: impl Fn(char) -> Option<T>,
+    ) -> Result<Self> {
+        let mut width = None;
+        let mut cells = Vec::new();
+        let mut height = 0;
+
+        for line in input.trim().lines() {
+            let expected = *width.get_or_insert(line.chars().count());
+            if line.chars().count() != expected {
+                return Err(Error::at(
+                    day,
+                    input,
+                    line,
+                    format!(
+                        "expected {} cells, found {}",
+                        expected,
+                        line.chars().count()
+                    ),
+                ));
+            }
+            for (i, c) in line.char_indices() {
+                let value = cell(c).ok_or_else(|| {
+                    Error::at(
+                        day,
+                        input,
+                        &line[i..],
+                        format!("expected {}, found `{}`", what, c),
+                    )
+                })?;
+                cells.push(value);
+            }
+            height += 1;
+        }
+
+        match width {
+            Some(width) if width > 0 => Ok(Grid::new(width, height, cells)),
+            _ => Err(Error::new(day, "the grid is empty")),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, (x, y): Point) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.contains(p).then(|| &self.cells[self.offset(p)])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        if !self.contains(p) {
+            return None;
+        }
+        let offset = self.offset(p);
+        Some(&mut self.cells[offset])
+    }
+
+    /// Replaces the cell at `p`, returning the old value, or `None` if `p` is out of bounds.
+    pub fn set(&mut self, p: Point, value: T) -> Option<T> {
+        self.get_mut(p).map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// Every position in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        points(self.width, self.height)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// The up to 4 positions sharing an edge with `p`.
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = Point> {
+        around(p, &ORTHOGONAL, self.width, self.height)
+    }
+
+    /// The up to 8 positions sharing an edge or a corner with `p`.
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = Point> {
+        around(p, &ALL_AROUND, self.width, self.height)
+    }
+
+    /// Every row, top to bottom, empty ones included for a grid of width 0.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.cells[x..].iter().step_by(self.width))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// Repeats the grid `across` times to the right and `down` times downwards.
+    ///
+    /// `f` receives each cell along with the `(x, y)` index of the copy it lands in.
+    pub fn tile(&self, across: usize, down: usize, f: impl Fn(&T, Point) -> T) -> Grid<T> {
+        Grid::from_fn(self.width * across, self.height * down, |(x, y)| {
+            let cell = &self[(x % self.width, y % self.height)];
+            f(cell, (x / self.width, y / self.height))
+        })
+    }
+
+    fn offset(&self, (x, y): Point) -> usize {
+        y * self.width + x
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Grid::new(width, height, vec![value; width * height])
+    }
+
+    /// Swaps rows and columns.
+    pub fn transpose(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |(x, y)| self[(y, x)].clone())
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |(x, y)| {
+            self[(self.width - 1 - x, y)].clone()
+        })
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |(x, y)| {
+            self[(x, self.height - 1 - y)].clone()
+        })
+    }
+
+    /// Turns the grid a quarter turn clockwise.
+    pub fn rotate_clockwise(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |(x, y)| {
+            self[(y, self.height - 1 - x)].clone()
+        })
+    }
+}
+
+impl<T: From<u8>> Grid<T> {
+    /// Parses a block of single digits, such as a height map.
+    pub fn parse_digits(day: u8, input: &str) -> Result<Self> {
+        Grid::parse(day, input, "a digit", |c| {
+            c.to_digit(10).map(|d| T::from(d as u8))
+        })
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", p))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", p))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn points(width: usize, height: usize) -> impl Iterator<Item = Point> {
+    (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+}
+
+fn around(
+    (x, y): Point,
+    offsets: &'static [(isize, isize)],
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = Point> {
+    offsets.iter().filter_map(move |&(dx, dy)| {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        (x < width && y < height).then_some((x, y))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "123\n456\n";
+
+    fn example() -> Grid<u8> {
+        Grid::parse_digits(0, EXAMPLE_INPUT).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let grid = example();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.to_string(), EXAMPLE_INPUT.trim());
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let err = Grid::<u8>::parse_digits(9, "123\n4x6\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 9, line 2, column 2: expected a digit, found `x`"
+        );
+
+        let err = Grid::<u8>::parse_digits(9, "123\n45\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 9, line 2, column 1: expected 3 cells, found 2"
+        );
+
+        let err = Grid::<u8>::parse_digits(9, "\n").unwrap_err();
+        assert_eq!(err.reason, "the grid is empty");
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = example();
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.set((0, 2), 0), None);
+        assert_eq!(grid.set((0, 1), 0), Some(4));
+        assert_eq!(grid[(0, 1)], 0);
+        assert_eq!(grid.get_mut((usize::MAX, usize::MAX)), None);
+        assert_eq!(grid.get((usize::MAX, usize::MAX)), None);
+    }
+
+    #[test]
+    fn test_zero_width() {
+        let grid = Grid::<u8>::new(0, 2, Vec::new());
+        assert_eq!(grid.rows().count(), 2);
+        assert!(grid.rows().all(|row| row.is_empty()));
+        assert_eq!(grid.columns().count(), 0);
+        assert_eq!(grid.to_string(), "\n");
+        assert_eq!(grid.get((0, 0)), None);
+
+        let grid = Grid::<u8>::new(3, 0, Vec::new());
+        assert_eq!(grid.rows().count(), 0);
+        assert_eq!(grid.to_string(), "");
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = example();
+        let corner: Vec<_> = grid.neighbours4((0, 0)).collect();
+        assert_eq!(corner, [(1, 0), (0, 1)]);
+        let middle: Vec<_> = grid.neighbours8((1, 0)).collect();
+        assert_eq!(middle, [(2, 0), (2, 1), (1, 1), (0, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = example();
+        let rows: Vec<_> = grid.rows().collect();
+        assert_eq!(rows, [[1, 2, 3], [4, 5, 6]]);
+        let columns: Vec<Vec<_>> = grid.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(columns, [[1, 4], [2, 5], [3, 6]]);
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = example();
+        assert_eq!(grid.transpose().to_string(), "14\n25\n36");
+        assert_eq!(grid.flip_horizontal().to_string(), "321\n654");
+        assert_eq!(grid.flip_vertical().to_string(), "456\n123");
+        assert_eq!(grid.rotate_clockwise().to_string(), "41\n52\n63");
+    }
+
+    #[test]
+    fn test_tile() {
+        let grid = example().tile(2, 2, |&n, (x, y)| n + 10 * (x + 2 * y) as u8);
+        assert_eq!(
+            grid.to_string(),
+            "123111213\n456141516\n212223313233\n242526343536"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::answers::PROBLEM;
+use crate::error::{Error, Result};
+
+/// Directory searched for `dN.txt` files when none is given.
+pub const DEFAULT_INPUTS_DIR: &str = "src/inputs";
+
+/// Environment variable overriding [`DEFAULT_INPUTS_DIR`].
+pub const INPUTS_DIR_VAR: &str = "ADVENT_INPUTS";
+
+/// Where a day's puzzle input is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A directory holding one `dN.txt` file per day.
+    Dir(PathBuf),
+    /// A single file, used whatever the day.
+    File(PathBuf),
+    Stdin,
+}
+
+impl Default for Source {
+    /// The inputs directory named by `ADVENT_INPUTS`, or `src/inputs`.
+    fn default() -> Self {
+        let dir = env::var_os(INPUTS_DIR_VAR).unwrap_or_else(|| DEFAULT_INPUTS_DIR.into());
+        Source::Dir(dir.into())
+    }
+}
+
+impl Source {
+    /// Reads `-` as stdin and anything else as a file path.
+    pub fn from_arg(arg: &str) -> Self {
+        match arg {
+            "-" => Source::Stdin,
+            path => Source::File(path.into()),
+        }
+    }
+
+    /// The name answers read from this source are recorded under: [`PROBLEM`] for
+    /// [`DEFAULT_INPUTS_DIR`], the path of any other directory, even one named by
+    /// `ADVENT_INPUTS`, and the file name without its extension for a single file.
+    pub fn name(&self) -> String {
+        match self {
+            Source::Dir(dir) if dir == Path::new(DEFAULT_INPUTS_DIR) => PROBLEM.to_string(),
+            Source::Dir(dir) => dir.display().to_string(),
+            Source::File(path) => path.file_stem().map_or_else(
+                || path.display().to_string(),
+                |stem| stem.to_string_lossy().into_owned(),
+            ),
+            Source::Stdin => "stdin".to_string(),
+        }
+    }
+
+    /// Reads the puzzle input for `day`.
+    pub fn load(&self, day: u8) -> Result<String> {
+        match self {
+            Source::Dir(dir) => {
+                let path = path_for(dir, day);
+                if !path.exists() {
+                    return Err(missing(day, &path));
+                }
+                read_file(day, &path)
+            }
+            Source::File(path) if !path.exists() => Err(missing(day, path)),
+            Source::File(path) => read_file(day, path),
+            Source::Stdin => {
+                let mut input = String::new();
+                io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(|err| Error::new(day, format!("cannot read stdin: {}", err)))?;
+                Ok(input)
+            }
+        }
+    }
+
+    /// Opens the puzzle input for `day` to be read as it arrives, instead of all at once.
+    pub fn open(&self, day: u8) -> Result<Box<dyn Read>> {
+        let path = match self {
+            Source::Dir(dir) => path_for(dir, day),
+            Source::File(path) => path.clone(),
+            Source::Stdin => return Ok(Box::new(io::stdin())),
+        };
+        if !path.exists() {
+            return Err(missing(day, &path));
+        }
+        match fs::File::open(&path) {
+            Ok(file) => Ok(Box::new(file)),
+            Err(err) => Err(unreadable(day, &path, err)),
+        }
+    }
+}
+
+/// The file holding `day`'s input inside an inputs directory.
+pub fn path_for(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("d{}.txt", day))
+}
+
+fn read_file(day: u8, path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|err| unreadable(day, path, err))
+}
+
+fn missing(day: u8, path: &Path) -> Error {
+    Error::new(day, format!("no input, {} does not exist", path.display()))
+}
+
+fn unreadable(day: u8, path: &Path, err: io::Error) -> Error {
+    Error::new(day, format!("cannot read {}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_from_dir() {
+        let source = Source::Dir(DEFAULT_INPUTS_DIR.into());
+        assert_eq!(source.load(7).unwrap(), include_str!("inputs/d7.txt"));
+    }
+
+    #[test]
+    fn test_open() {
+        let mut input = String::new();
+        let source = Source::Dir(DEFAULT_INPUTS_DIR.into());
+        source.open(7).unwrap().read_to_string(&mut input).unwrap();
+        assert_eq!(input, include_str!("inputs/d7.txt"));
+
+        let err = Source::Dir("missing".into()).open(7).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "day 7: no input, missing/d7.txt does not exist"
+        );
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let source = Source::from_arg("src/inputs/d13_example.txt");
+        assert_eq!(
+            source.load(13).unwrap(),
+            include_str!("inputs/d13_example.txt")
+        );
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let source = Source::Dir("does/not/exist".into());
+        let err = source.load(3).unwrap_err();
+        assert_eq!(err.day, Some(3));
+        assert_eq!(
+            err.to_string(),
+            "day 3: no input, does/not/exist/d3.txt does not exist"
+        );
+    }
+
+    #[test]
+    fn test_stdin_arg() {
+        assert_eq!(Source::from_arg("-"), Source::Stdin);
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(Source::Dir(DEFAULT_INPUTS_DIR.into()).name(), PROBLEM);
+        assert_eq!(Source::Dir("tests/inputs".into()).name(), "tests/inputs");
+        assert_eq!(
+            Source::from_arg("src/inputs/d13_example.txt").name(),
+            "d13_example"
+        );
+        assert_eq!(Source::Stdin.name(), "stdin");
+    }
+}
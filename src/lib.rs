@@ -14,7 +14,15 @@ pub mod d12;
 pub mod d13;
 pub mod d14;
 pub mod d15;
-//pub mod d16;
-pub mod d16_2;
+pub mod d16;
 pub mod d17;
 pub mod d18;
+pub mod d19;
+
+pub mod answers;
+pub mod error;
+pub mod examples;
+pub mod grid;
+pub mod input;
+pub mod search;
+pub mod solution;
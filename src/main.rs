@@ -0,0 +1,289 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::time::{Duration, Instant};
+
+use advent::answers::{Answers, Verdict, ANSWERS_FILE};
+use advent::d16::compile::{compile_to_hex, Versions};
+use advent::d16::{disasm, parse_with_layout, stream, vm, LengthType};
+use advent::error::Error;
+use advent::examples;
+use advent::input::Source;
+use advent::solution::{self, Answer, Day, REGISTRY};
+
+const USAGE: &str = "usage:
+    advent list
+    advent run <day> [part] [--input <file>|-] [--inputs <dir>]
+    advent run --all [--inputs <dir>]
+    advent verify [--inputs <dir>]
+    advent examples [day]
+    advent record <day> [part] [--input <file>|-] [--inputs <dir>]
+    advent compile <expression> [--count]
+    advent disassemble [--json] [--input <file>|-] [--inputs <dir>]
+    advent bytecode [--input <file>|-] [--inputs <dir>]
+    advent transmissions [--input <file>|-] [--inputs <dir>]
+
+Inputs are read from <dir>/dN.txt, where <dir> defaults to $ADVENT_INPUTS or src/inputs.
+`--input -` reads the input from stdin.
+Confirmed answers are kept in answers.json: `verify` checks every day against it and
+`record` stores the answers a day currently gives, both keyed by the input they read:
+the default inputs directory, another <dir>, or the name of a single <file>.
+`examples` checks days against the worked examples of their puzzle text, dN.md.
+`compile` writes a day 16 transmission for an expression such as `1 + 2*3 + (4 > 5)` or
+`(sum 1 (product 2 3) (gt 4 5))`; `--count` gives operators length type 1.
+`disassemble` prints the packets of a day 16 transmission with their bit offsets, and
+`bytecode` the stack machine program that evaluates it.
+`transmissions` decodes one day 16 transmission per line, as the lines are read.";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2)
+}
+
+fn fail(err: Error) -> ! {
+    eprintln!("{}", err);
+    process::exit(1)
+}
+
+fn parse_arg(arg: Option<&String>, what: &str) -> u8 {
+    let arg = arg.unwrap_or_else(|| usage());
+    arg.parse().unwrap_or_else(|_| {
+        eprintln!("invalid {}: {}", what, arg);
+        usage()
+    })
+}
+
+/// Removes `--input`/`--inputs` and their values from `args`, returning the selected source.
+fn take_source(args: &mut Vec<String>) -> Source {
+    let mut source = Source::default();
+    while let Some(i) = args.iter().position(|a| a == "--input" || a == "--inputs") {
+        if i + 1 >= args.len() {
+            usage();
+        }
+        let value = args.remove(i + 1);
+        source = match args.remove(i).as_str() {
+            "--input" => Source::from_arg(&value),
+            _ => Source::Dir(value.into()),
+        };
+    }
+    source
+}
+
+fn solve(day: &Day, part: u8, input: &str) -> Answer {
+    day.run(part, input).unwrap_or_else(|err| fail(err))
+}
+
+/// Parses the optional part argument at `args[i]`, defaulting to both parts.
+fn parts_arg(args: &[String], i: usize, day: &Day) -> Vec<u8> {
+    match args.get(i).map(|_| parse_arg(args.get(i), "part")) {
+        Some(part @ (1 | 2)) => vec![part],
+        Some(part) => fail(Error::other(format!(
+            "no part {} for day {}",
+            part, day.day
+        ))),
+        None => vec![1, 2],
+    }
+}
+
+fn run(day: &Day, part: u8, input: &str) -> Duration {
+    let start = Instant::now();
+    let answer = solve(day, part, input).to_string();
+    let elapsed = start.elapsed();
+
+    // multi-line answers (day 13) start on their own line
+    let sep = if answer.contains('\n') { "\n" } else { " " };
+    println!(
+        "day {:>2} part {}:{}{} ({:.2?})",
+        day.day, part, sep, answer, elapsed
+    );
+    elapsed
+}
+
+fn day_arg(arg: Option<&String>) -> &'static Day {
+    let day = parse_arg(arg, "day");
+    solution::get(day).unwrap_or_else(|| fail(Error::other(format!("no solution for day {}", day))))
+}
+
+fn load_answers() -> Answers {
+    Answers::load(Path::new(ANSWERS_FILE)).unwrap_or_else(|err| fail(err))
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let source = take_source(&mut args);
+
+    match args.first().map(String::as_str) {
+        Some("list") => {
+            for day in REGISTRY {
+                println!("day {:>2}", day.day);
+            }
+        }
+        Some("run") if args.get(1).map(String::as_str) == Some("--all") => {
+            if !matches!(source, Source::Dir(_)) {
+                fail(Error::other(
+                    "--all reads one file per day, use --inputs <dir>",
+                ));
+            }
+            let mut total = Duration::ZERO;
+            for day in REGISTRY {
+                match source.load(day.day) {
+                    Ok(input) => total += run(day, 1, &input) + run(day, 2, &input),
+                    Err(err) => eprintln!("{}", err),
+                }
+            }
+            println!("total: {:.2?}", total);
+        }
+        Some("run") => {
+            let day = day_arg(args.get(1));
+            let input = source.load(day.day).unwrap_or_else(|err| fail(err));
+            for part in parts_arg(&args, 2, day) {
+                run(day, part, &input);
+            }
+        }
+        Some("verify") => {
+            if !matches!(source, Source::Dir(_)) {
+                fail(Error::other(
+                    "verify reads one file per day, use --inputs <dir>",
+                ));
+            }
+            let answers = load_answers();
+            let name = source.name();
+            let mut wrong = 0;
+            for day in REGISTRY {
+                let input = match source.load(day.day) {
+                    Ok(input) => input,
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        continue;
+                    }
+                };
+                for part in 1..=2 {
+                    let answer = solve(day, part, &input);
+                    let verdict = match answers.verify(day.day, &name, part, &answer) {
+                        Verdict::Correct => "ok".to_string(),
+                        Verdict::Wrong { expected } => {
+                            wrong += 1;
+                            format!("WRONG, expected {} but got {}", expected, answer)
+                        }
+                        Verdict::Unverified => format!("unverified, got {}", answer),
+                    };
+                    println!("day {:>2} part {}: {}", day.day, part, verdict);
+                }
+            }
+            let unverified = answers.unverified(REGISTRY, &name);
+            println!("{} wrong, {} unverified", wrong, unverified.len());
+            if wrong > 0 {
+                process::exit(1);
+            }
+        }
+        Some("examples") => {
+            let days: Vec<&Day> = match args.get(1) {
+                Some(_) => vec![day_arg(args.get(1))],
+                None => REGISTRY.iter().collect(),
+            };
+            let mut wrong = 0;
+            for day in days {
+                let path = examples::path_for(day.day);
+                let markdown = match fs::read_to_string(&path) {
+                    Ok(markdown) => markdown,
+                    Err(_) if args.get(1).is_none() => continue,
+                    Err(err) => fail(Error::other(format!(
+                        "cannot read {}: {}",
+                        path.display(),
+                        err
+                    ))),
+                };
+                for example in
+                    examples::examples(day.day, &markdown).unwrap_or_else(|err| fail(err))
+                {
+                    let answer = solve(day, example.part, &example.input);
+                    let verdict = if answer == example.expected {
+                        "ok".to_string()
+                    } else {
+                        wrong += 1;
+                        format!("WRONG, expected {} but got {}", example.expected, answer)
+                    };
+                    println!(
+                        "day {:>2} part {} ({}:{}): {}",
+                        day.day,
+                        example.part,
+                        path.display(),
+                        example.line,
+                        verdict
+                    );
+                }
+            }
+            if wrong > 0 {
+                process::exit(1);
+            }
+        }
+        Some("record") => {
+            let day = day_arg(args.get(1));
+            let input = source.load(day.day).unwrap_or_else(|err| fail(err));
+            let mut answers = load_answers();
+            for part in parts_arg(&args, 2, day) {
+                let answer = solve(day, part, &input);
+                println!("day {:>2} part {}: recorded {}", day.day, part, answer);
+                answers.record(day.day, &source.name(), part, answer);
+            }
+            answers
+                .save(Path::new(ANSWERS_FILE))
+                .unwrap_or_else(|err| fail(err));
+        }
+        Some("compile") => {
+            let length_type = match args.iter().position(|a| a == "--count") {
+                Some(i) => {
+                    args.remove(i);
+                    LengthType::Count
+                }
+                None => LengthType::Bits,
+            };
+            let source = args.get(1).unwrap_or_else(|| usage());
+            let hex = compile_to_hex(source, Versions::Counting, length_type)
+                .unwrap_or_else(|err| fail(err));
+            println!("{}", hex);
+        }
+        Some("disassemble") => {
+            let input = source.load(16).unwrap_or_else(|err| fail(err));
+            let tree = disasm::disassemble(&input).unwrap_or_else(|err| fail(err));
+            match args.get(1).map(String::as_str) {
+                Some("--json") => println!("{}", tree.to_json()),
+                Some(_) => usage(),
+                None => print!("{}", tree),
+            }
+        }
+        Some("bytecode") => {
+            let input = source.load(16).unwrap_or_else(|err| fail(err));
+            let (packet, layouts) = parse_with_layout(&input).unwrap_or_else(|err| fail(err));
+            let program = vm::compile(&packet);
+            print!("{}", program);
+            let value = program
+                .run()
+                .unwrap_or_else(|err| fail(err.to_error(&layouts)));
+            println!("= {}", value);
+        }
+        Some("transmissions") => {
+            let reader = source.open(16).unwrap_or_else(|err| fail(err));
+            let mut failed = 0;
+            for transmission in stream::transmissions(reader) {
+                match transmission.packet {
+                    Ok(packet) => println!(
+                        "line {}: {} (version sum {})",
+                        transmission.line,
+                        packet.big_value(),
+                        packet.version_sum()
+                    ),
+                    Err(err) => {
+                        failed += 1;
+                        println!("{}", err);
+                    }
+                }
+            }
+            if failed > 0 {
+                process::exit(1);
+            }
+        }
+        _ => usage(),
+    }
+}
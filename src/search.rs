@@ -0,0 +1,254 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Shortest path by number of steps from `start` to the first node satisfying `is_goal`.
+///
+/// The returned path starts with `start` and ends with the goal.
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::from([start.clone()]);
+    parents.insert(start.clone(), None);
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            return Some(reconstruct_path(&parents, node));
+        }
+        for next in neighbours(&node) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Cheapest path from `start` to the first node satisfying `is_goal`, with its total cost.
+///
+/// `successors` yields each neighbour with the cost of moving to it, which must not be negative.
+pub fn dijkstra<N, C, I>(
+    start: N,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+/// [`dijkstra`], guided by `heuristic`, an estimate of the remaining cost to a goal.
+///
+/// The path found is the cheapest as long as the heuristic never overestimates.
+pub fn astar<N, C, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut best = HashMap::new();
+    let mut parents = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+
+    best.insert(start.clone(), C::default());
+    parents.insert(start.clone(), None);
+    frontier.push(Visit {
+        estimate: heuristic(&start),
+        cost: C::default(),
+        node: start,
+    });
+
+    while let Some(Visit { cost, node, .. }) = frontier.pop() {
+        if is_goal(&node) {
+            return Some((reconstruct_path(&parents, node), cost));
+        }
+        if best.get(&node).is_some_and(|&known| known < cost) {
+            // a cheaper way here was already expanded
+            continue;
+        }
+        for (next, step) in successors(&node) {
+            let cost = cost + step;
+            if best.get(&next).is_none_or(|&known| cost < known) {
+                best.insert(next.clone(), cost);
+                parents.insert(next.clone(), Some(node.clone()));
+                frontier.push(Visit {
+                    estimate: cost + heuristic(&next),
+                    cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Walks `parents` back from `goal` to the node without a parent.
+///
+/// The returned path starts at that root and ends with `goal`.
+pub fn reconstruct_path<N>(parents: &HashMap<N, Option<N>>, goal: N) -> Vec<N>
+where
+    N: Eq + Hash + Clone,
+{
+    let mut path = vec![goal];
+    while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Number of distinct paths from `start` to nodes satisfying `is_goal`.
+///
+/// Goals are not expanded further. Counts are memoised per node, so the graph reachable from
+/// `start` must not have cycles; encode whatever makes a walk unique (e.g. visited nodes)
+/// into the node itself.
+pub fn count_paths<N, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> usize
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    fn count<N, I>(
+        node: N,
+        successors: &mut impl FnMut(&N) -> I,
+        is_goal: &mut impl FnMut(&N) -> bool,
+        memo: &mut HashMap<N, usize>,
+    ) -> usize
+    where
+        N: Eq + Hash + Clone,
+        I: IntoIterator<Item = N>,
+    {
+        if is_goal(&node) {
+            return 1;
+        }
+        if let Some(&paths) = memo.get(&node) {
+            return paths;
+        }
+        let paths = successors(&node)
+            .into_iter()
+            .map(|next| count(next, successors, is_goal, memo))
+            .sum();
+        memo.insert(node, paths);
+        paths
+    }
+
+    count(start, &mut successors, &mut is_goal, &mut HashMap::new())
+}
+
+/// An entry of the A* frontier, ordered so the heap pops the lowest estimate first.
+struct Visit<N, C> {
+    estimate: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> Ord for Visit<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed for a min-heap; on ties prefer the node that got further
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl<N, C: Ord> PartialOrd for Visit<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> PartialEq for Visit<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N, C: Ord> Eq for Visit<N, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -1- 1 -1- 2
+    //  \          |
+    //   -----5----3 -1- 4
+    fn weighted(n: &u32) -> Vec<(u32, u32)> {
+        match n {
+            0 => vec![(1, 1), (3, 5)],
+            1 => vec![(0, 1), (2, 1)],
+            2 => vec![(1, 1), (3, 1)],
+            3 => vec![(0, 5), (2, 1), (4, 1)],
+            _ => vec![(3, 1)],
+        }
+    }
+
+    fn unweighted(n: &u32) -> Vec<u32> {
+        weighted(n).into_iter().map(|(next, _)| next).collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        assert_eq!(bfs(0, unweighted, |&n| n == 4), Some(vec![0, 3, 4]));
+        assert_eq!(bfs(0, unweighted, |&n| n == 9), None);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        assert_eq!(
+            dijkstra(0, weighted, |&n| n == 4),
+            Some((vec![0, 1, 2, 3, 4], 4))
+        );
+        assert_eq!(dijkstra(2, weighted, |&n| n == 2), Some((vec![2], 0)));
+    }
+
+    #[test]
+    fn test_astar() {
+        let heuristic = |&n: &u32| 4 - n;
+        assert_eq!(
+            astar(0, weighted, heuristic, |&n| n == 4),
+            Some((vec![0, 1, 2, 3, 4], 4))
+        );
+    }
+
+    #[test]
+    fn test_reconstruct_path() {
+        let parents = HashMap::from([('a', None), ('b', Some('a')), ('c', Some('b'))]);
+        assert_eq!(reconstruct_path(&parents, 'c'), ['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn test_count_paths() {
+        // lattice paths through a 10x10 grid of steps
+        let paths = count_paths(
+            (0, 0),
+            |&(x, y)| {
+                vec![(x + 1, y), (x, y + 1)]
+                    .into_iter()
+                    .filter(|&(x, y)| x <= 10 && y <= 10)
+            },
+            |&p| p == (10, 10),
+        );
+        assert_eq!(paths, 184756);
+    }
+}
@@ -0,0 +1,173 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::*;
+
+/// The answer to one part of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Number(i64),
+    /// A number above `i64::MAX`: any smaller one is a `Number`, so answers compare equal
+    /// whatever type they were computed in.
+    Unsigned(u64),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Unsigned(n) => write!(f, "{}", n),
+            Answer::Text(s) => f.write_str(s),
+        }
+    }
+}
+
+macro_rules! answer_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Number(n.into())
+                }
+            }
+        )*
+    };
+}
+
+answer_from_number!(i32, i64, u32);
+
+macro_rules! answer_from_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    match i64::try_from(n) {
+                        Ok(n) => Answer::Number(n),
+                        Err(_) => Answer::Unsigned(n as u64),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+answer_from_unsigned!(u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+/// A day of the calendar: how to read its puzzle input and how to answer both parts.
+pub trait Solution {
+    const DAY: u8;
+
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Result<Answer>;
+    fn part2(input: &Self::Input) -> Result<Answer>;
+}
+
+/// A type-erased [`Solution`], so days with different inputs can live side by side.
+pub struct Day {
+    pub day: u8,
+    pub part1: fn(&str) -> Result<Answer>,
+    pub part2: fn(&str) -> Result<Answer>,
+}
+
+impl Day {
+    pub const fn of<S: Solution>() -> Self {
+        Day {
+            day: S::DAY,
+            part1: |input| S::part1(&S::parse(input)?),
+            part2: |input| S::part2(&S::parse(input)?),
+        }
+    }
+
+    /// Runs `part` (1 or 2) on `input`.
+    pub fn run(&self, part: u8, input: &str) -> Result<Answer> {
+        match part {
+            1 => (self.part1)(input),
+            2 => (self.part2)(input),
+            _ => Err(Error::new(self.day, format!("there is no part {}", part))),
+        }
+    }
+}
+
+/// Every solved day, ordered by day number.
+pub const REGISTRY: &[Day] = &[
+    Day::of::<d1::Day1>(),
+    Day::of::<d2::Day2>(),
+    Day::of::<d3::Day3>(),
+    Day::of::<d4::Day4>(),
+    Day::of::<d5::Day5>(),
+    Day::of::<d6::Day6>(),
+    Day::of::<d7::Day7>(),
+    Day::of::<d8::Day8>(),
+    Day::of::<d9::Day9>(),
+    Day::of::<d10::Day10>(),
+    Day::of::<d11::Day11>(),
+    Day::of::<d12::Day12>(),
+    Day::of::<d13::Day13>(),
+    Day::of::<d14::Day14>(),
+    Day::of::<d15::Day15>(),
+    Day::of::<d16::Day16>(),
+    Day::of::<d17::Day17>(),
+    Day::of::<d18::Day18>(),
+    Day::of::<d19::Day19>(),
+];
+
+/// Looks up the solution registered for `day`.
+pub fn get(day: u8) -> Option<&'static Day> {
+    REGISTRY.iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_is_ordered_by_day() {
+        let days: Vec<u8> = REGISTRY.iter().map(|d| d.day).collect();
+        assert_eq!(days, (1..=19).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_run_through_registry() {
+        let day = get(7).unwrap();
+        let input = "16,1,2,0,4,2,7,1,2,14";
+        assert_eq!(day.run(1, input), Ok(Answer::Number(37)));
+        assert_eq!(day.run(2, input), Ok(Answer::Number(168)));
+        assert!(day.run(3, input).is_err());
+    }
+
+    #[test]
+    fn test_unsigned_answers() {
+        assert_eq!(Answer::from(7u64), Answer::Number(7));
+        assert_eq!(Answer::from(i64::MAX as u64), Answer::Number(i64::MAX));
+        let max = Answer::from(u64::MAX);
+        assert_eq!(max, Answer::Unsigned(u64::MAX));
+        assert_eq!(max.to_string(), "18446744073709551615");
+
+        let json = serde_json::to_string(&[Answer::Number(-1), max.clone()]).unwrap();
+        assert_eq!(json, "[-1,18446744073709551615]");
+        assert_eq!(
+            serde_json::from_str::<Vec<Answer>>(&json).unwrap(),
+            [Answer::Number(-1), max]
+        );
+
+        // a day 16 transmission of a single literal, 2^64 - 1
+        let day = get(16).unwrap();
+        assert_eq!(
+            day.run(2, "13FFFFFFFFFFFFFFFFFFBC"),
+            Ok(Answer::Unsigned(u64::MAX))
+        );
+    }
+}